@@ -1,5 +1,6 @@
 use crate::config::Config;
 use crate::error::{AppError, Result};
+use crate::storage::{UploadCheckpoint, UploadCheckpoints, UploadMetadata};
 use reqwest::blocking::Client as HttpClient;
 use see_sdk::{
     client::Client,
@@ -15,9 +16,16 @@ use see_sdk::{
     },
 };
 use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
 use std::path::Path;
 use std::time::Duration;
 
+/// Files at least this large are sent in chunks when the server supports it
+const CHUNKED_UPLOAD_THRESHOLD: u64 = 16 * 1024 * 1024;
+const DEFAULT_CHUNK_SIZE: u64 = 8 * 1024 * 1024;
+const MAX_CHUNK_SIZE: u64 = 64 * 1024 * 1024;
+
 /// Text type for S.EE API
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -48,10 +56,36 @@ struct ExtendedCreateTextRequest {
     domain: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     text_type: Option<String>,
+    /// Highlighting language, ignored by servers that do not support it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    syntax: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     custom_slug: Option<String>,
 }
 
+/// Request that opens a chunked upload session
+#[derive(Debug, Serialize)]
+struct ChunkedUploadRequest {
+    filename: String,
+    size: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkedUploadResponse {
+    data: ChunkedUploadSession,
+}
+
+/// Server side state of a chunked upload
+#[derive(Debug, Deserialize)]
+struct ChunkedUploadSession {
+    upload_id: String,
+    #[serde(default)]
+    chunk_size: Option<u64>,
+    /// Bytes the server has received so far
+    #[serde(default)]
+    offset: u64,
+}
+
 pub struct ApiClient {
     client: Client,
     http_client: HttpClient,
@@ -136,6 +170,7 @@ impl ApiClient {
         title: &str,
         domain: Option<&str>,
         text_type: Option<TextType>,
+        syntax: Option<&str>,
     ) -> Result<CreateTextResponse> {
         // Use direct API call to support domain and type
         let request = ExtendedCreateTextRequest {
@@ -143,6 +178,7 @@ impl ApiClient {
             title: Some(title.to_string()), // API requires title
             domain: domain.map(String::from),
             text_type: text_type.map(|t| t.as_str().to_string()),
+            syntax: syntax.map(String::from),
             custom_slug: None,
         };
 
@@ -166,6 +202,23 @@ impl ApiClient {
             .map_err(|e: reqwest::Error| AppError::Api(e.to_string()))
     }
 
+    /// Download the raw content of a text share
+    pub fn fetch_text(&self, raw_url: &str) -> Result<String> {
+        let response = self
+            .http_client
+            .get(raw_url)
+            .send()
+            .map_err(|e: reqwest::Error| AppError::Api(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::Api(format!("HTTP error {}", response.status())));
+        }
+
+        response
+            .text()
+            .map_err(|e: reqwest::Error| AppError::Api(e.to_string()))
+    }
+
     pub fn delete_text(&self, domain: &str, slug: &str) -> Result<()> {
         let request = DeleteTextRequest {
             domain: domain.to_string(),
@@ -180,6 +233,215 @@ impl ApiClient {
         self.client.upload_file(path).map_err(AppError::from)
     }
 
+    /// Upload a file in resumable chunks when it is large and the server
+    /// supports it, otherwise in a single request.
+    ///
+    /// Progress is checkpointed under the data directory after every chunk,
+    /// so calling this again for the same file continues where it stopped.
+    pub fn upload_file_resumable(
+        &self,
+        path: &Path,
+        metadata: &UploadMetadata,
+    ) -> Result<FileUploadResponse> {
+        let checkpoints = UploadCheckpoints::load()?;
+        self.upload_file_chunked(&checkpoints, CHUNKED_UPLOAD_THRESHOLD, path, metadata)
+    }
+
+    /// `upload_file_resumable` with the checkpoint store and size threshold given
+    fn upload_file_chunked(
+        &self,
+        checkpoints: &UploadCheckpoints,
+        threshold: u64,
+        path: &Path,
+        metadata: &UploadMetadata,
+    ) -> Result<FileUploadResponse> {
+        let size = fs::metadata(path)?.len();
+        if size < threshold {
+            return self.upload_file(path);
+        }
+
+        let mut checkpoint = match self.resume_checkpoint(checkpoints, path)? {
+            Some(checkpoint) => checkpoint,
+            None => {
+                let filename = path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("file");
+                let Some(session) = self.start_chunked_upload(filename, size)? else {
+                    // No chunked protocol on this server
+                    return self.upload_file(path);
+                };
+                let chunk_size = session
+                    .chunk_size
+                    .unwrap_or(DEFAULT_CHUNK_SIZE)
+                    .clamp(1, MAX_CHUNK_SIZE);
+                let checkpoint = UploadCheckpoint::new(
+                    session.upload_id,
+                    path.to_path_buf(),
+                    size,
+                    chunk_size,
+                    metadata.clone(),
+                );
+                checkpoints.save(&checkpoint)?;
+                checkpoint
+            }
+        };
+
+        let mut file = File::open(path)?;
+        file.seek(SeekFrom::Start(checkpoint.offset))?;
+        let mut buffer = vec![0; checkpoint.chunk_size as usize];
+
+        while checkpoint.offset < checkpoint.size {
+            let len = (checkpoint.size - checkpoint.offset).min(checkpoint.chunk_size) as usize;
+            file.read_exact(&mut buffer[..len])?;
+            self.upload_chunk(&checkpoint, &buffer[..len])?;
+            checkpoint.offset += len as u64;
+            checkpoints.save(&checkpoint)?;
+        }
+
+        let response = self.finish_chunked_upload(&checkpoint.upload_id)?;
+        checkpoints.remove(&checkpoint.upload_id)?;
+        Ok(response)
+    }
+
+    /// Find the checkpoint for a file and sync its offset with the server.
+    /// Sessions the server no longer knows about are dropped.
+    fn resume_checkpoint(
+        &self,
+        checkpoints: &UploadCheckpoints,
+        path: &Path,
+    ) -> Result<Option<UploadCheckpoint>> {
+        let Some(mut checkpoint) = checkpoints.find(path) else {
+            return Ok(None);
+        };
+
+        let response = self
+            .http_client
+            .get(self.chunked_url(&checkpoint.upload_id))
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .send()
+            .map_err(|e: reqwest::Error| AppError::Api(e.to_string()))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            checkpoints.remove(&checkpoint.upload_id)?;
+            return Ok(None);
+        }
+        let session = Self::parse_chunked_response(response)?;
+        if session.offset > checkpoint.size {
+            checkpoints.remove(&checkpoint.upload_id)?;
+            return Ok(None);
+        }
+
+        checkpoint.offset = session.offset;
+        Ok(Some(checkpoint))
+    }
+
+    /// Open a chunked upload session, or `None` if the server has no such endpoint
+    fn start_chunked_upload(
+        &self,
+        filename: &str,
+        size: u64,
+    ) -> Result<Option<ChunkedUploadSession>> {
+        let request = ChunkedUploadRequest {
+            filename: filename.to_string(),
+            size,
+        };
+
+        let response = self
+            .http_client
+            .post(format!("{}/file/upload/chunked", self.base_url))
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .json(&request)
+            .send()
+            .map_err(|e: reqwest::Error| AppError::Api(e.to_string()))?;
+
+        match response.status() {
+            reqwest::StatusCode::NOT_FOUND
+            | reqwest::StatusCode::METHOD_NOT_ALLOWED
+            | reqwest::StatusCode::NOT_IMPLEMENTED => Ok(None),
+            _ => Self::parse_chunked_response(response).map(Some),
+        }
+    }
+
+    fn upload_chunk(&self, checkpoint: &UploadCheckpoint, chunk: &[u8]) -> Result<()> {
+        let start = checkpoint.offset;
+        let end = start + chunk.len() as u64 - 1;
+
+        let response = self
+            .http_client
+            .put(self.chunked_url(&checkpoint.upload_id))
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .header("Content-Type", "application/octet-stream")
+            .header(
+                "Content-Range",
+                format!("bytes {}-{}/{}", start, end, checkpoint.size),
+            )
+            .body(chunk.to_vec())
+            .send()
+            .map_err(|e: reqwest::Error| AppError::Api(e.to_string()))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().unwrap_or_default();
+            return Err(AppError::Api(format!("API error {}: {}", status, text)));
+        }
+        Ok(())
+    }
+
+    fn finish_chunked_upload(&self, upload_id: &str) -> Result<FileUploadResponse> {
+        let response = self
+            .http_client
+            .post(format!("{}/complete", self.chunked_url(upload_id)))
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .send()
+            .map_err(|e: reqwest::Error| AppError::Api(e.to_string()))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().unwrap_or_default();
+            return Err(AppError::Api(format!("API error {}: {}", status, text)));
+        }
+
+        response
+            .json::<FileUploadResponse>()
+            .map_err(|e: reqwest::Error| AppError::Api(e.to_string()))
+    }
+
+    fn chunked_url(&self, upload_id: &str) -> String {
+        format!("{}/file/upload/chunked/{}", self.base_url, upload_id)
+    }
+
+    fn parse_chunked_response(response: reqwest::blocking::Response) -> Result<ChunkedUploadSession> {
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().unwrap_or_default();
+            return Err(AppError::Api(format!("API error {}: {}", status, text)));
+        }
+
+        response
+            .json::<ChunkedUploadResponse>()
+            .map(|r| r.data)
+            .map_err(|e: reqwest::Error| AppError::Api(e.to_string()))
+    }
+
+    /// Download the contents of an uploaded file
+    pub fn fetch_file(&self, url: &str) -> Result<Vec<u8>> {
+        let response = self
+            .http_client
+            .get(url)
+            .send()
+            .map_err(|e: reqwest::Error| AppError::Api(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::Api(format!("HTTP error {}", response.status())));
+        }
+
+        response
+            .bytes()
+            .map(|b| b.to_vec())
+            .map_err(|e: reqwest::Error| AppError::Api(e.to_string()))
+    }
+
     pub fn delete_file(&self, key: &str) -> Result<()> {
         self.client.delete_file(key).map_err(AppError::from)?;
         Ok(())
@@ -214,6 +476,10 @@ pub mod async_bridge {
             title: String,
             domain: Option<String>,
             text_type: Option<TextType>,
+            syntax: Option<String>,
+        },
+        FetchText {
+            raw_url: String,
         },
         DeleteText {
             domain: String,
@@ -222,6 +488,10 @@ pub mod async_bridge {
         // File operations
         UploadFile {
             path: PathBuf,
+            metadata: UploadMetadata,
+        },
+        FetchFile {
+            url: String,
         },
         DeleteFile {
             key: String,
@@ -238,9 +508,11 @@ pub mod async_bridge {
         DeleteUrl(Result<()>),
         // Text operations
         CreateText(Result<CreateTextResponse>),
+        FetchText(Result<String>),
         DeleteText(Result<()>),
         // File operations
         UploadFile(Result<FileUploadResponse>),
+        FetchFile(Result<Vec<u8>>),
         DeleteFile(Result<()>),
     }
 
@@ -277,18 +549,26 @@ pub mod async_bridge {
                         title,
                         domain,
                         text_type,
+                        syntax,
                     } => ApiResponse::CreateText(client.create_text(
                         &content,
                         &title,
                         domain.as_deref(),
                         text_type,
+                        syntax.as_deref(),
                     )),
+                    ApiRequest::FetchText { raw_url } => {
+                        ApiResponse::FetchText(client.fetch_text(&raw_url))
+                    }
                     ApiRequest::DeleteText { domain, slug } => {
                         ApiResponse::DeleteText(client.delete_text(&domain, &slug))
                     }
                     // File operations
-                    ApiRequest::UploadFile { path } => {
-                        ApiResponse::UploadFile(client.upload_file(&path))
+                    ApiRequest::UploadFile { path, metadata } => {
+                        ApiResponse::UploadFile(client.upload_file_resumable(&path, &metadata))
+                    }
+                    ApiRequest::FetchFile { url } => {
+                        ApiResponse::FetchFile(client.fetch_file(&url))
                     }
                     ApiRequest::DeleteFile { key } => {
                         ApiResponse::DeleteFile(client.delete_file(&key))
@@ -301,8 +581,10 @@ pub mod async_bridge {
                     ApiRequest::ShortenUrl { .. } => ApiResponse::ShortenUrl(Err(e)),
                     ApiRequest::DeleteUrl { .. } => ApiResponse::DeleteUrl(Err(e)),
                     ApiRequest::CreateText { .. } => ApiResponse::CreateText(Err(e)),
+                    ApiRequest::FetchText { .. } => ApiResponse::FetchText(Err(e)),
                     ApiRequest::DeleteText { .. } => ApiResponse::DeleteText(Err(e)),
                     ApiRequest::UploadFile { .. } => ApiResponse::UploadFile(Err(e)),
+                    ApiRequest::FetchFile { .. } => ApiResponse::FetchFile(Err(e)),
                     ApiRequest::DeleteFile { .. } => ApiResponse::DeleteFile(Err(e)),
                 },
             };
@@ -313,3 +595,207 @@ pub mod async_bridge {
         receiver
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{StubRequest, StubResponse, StubServer, temp_dir};
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+
+    const FILE_SIZE: usize = 10_000;
+    const THRESHOLD: u64 = 1_000;
+
+    const UPLOADED: &str = r#"{"code":200,"message":"success","data":{"delete":"d",
+        "file_id":1,"filename":"big.bin","hash":"h","path":"/big.bin","size":10000,
+        "storename":"big.bin","upload_status":1,"url":"https://i.s.ee/big.bin"}}"#;
+
+    /// What a chunked upload server has received
+    #[derive(Default)]
+    struct Session {
+        requests: Vec<String>,
+        ranges: Vec<String>,
+        received: Vec<u8>,
+        cut_off: bool,
+    }
+
+    fn session_json(upload_id: &str, offset: usize) -> String {
+        format!(
+            r#"{{"data":{{"upload_id":"{}","chunk_size":4000,"offset":{}}}}}"#,
+            upload_id, offset
+        )
+    }
+
+    /// Answer the chunked protocol for `upload_id`, cutting the connection
+    /// 1500 bytes into the chunk at `cut_at`
+    fn chunked_server(
+        session: Arc<Mutex<Session>>,
+        upload_id: &'static str,
+        cut_at: Option<&'static str>,
+    ) -> StubServer {
+        StubServer::start(move |request: &StubRequest| {
+            let mut session = session.lock().unwrap();
+            session
+                .requests
+                .push(format!("{} {}", request.method, request.path));
+            let session_path = format!("/file/upload/chunked/{}", upload_id);
+
+            match (request.method.as_str(), request.path.as_str()) {
+                ("POST", "/file/upload/chunked") => {
+                    Some(StubResponse::json(200, &session_json(upload_id, 0)))
+                }
+                ("GET", path) if path == session_path => Some(StubResponse::json(
+                    200,
+                    &session_json(upload_id, session.received.len()),
+                )),
+                ("PUT", path) if path == session_path => {
+                    let range = request.header("Content-Range").unwrap_or_default();
+                    let range = range.split('-').next().unwrap_or_default().to_string();
+                    if cut_at.is_some_and(|cut| range == cut) && !session.cut_off {
+                        session.cut_off = true;
+                        session.received.extend_from_slice(&request.body[..1500]);
+                        return None;
+                    }
+                    session
+                        .ranges
+                        .push(request.header("Content-Range").unwrap().to_string());
+                    session.received.extend_from_slice(&request.body);
+                    Some(StubResponse::json(
+                        200,
+                        &session_json(upload_id, session.received.len()),
+                    ))
+                }
+                ("POST", path) if path == format!("{}/complete", session_path) => {
+                    Some(StubResponse::json(200, UPLOADED))
+                }
+                _ => Some(StubResponse::new(404)),
+            }
+        })
+    }
+
+    fn client(server: &StubServer) -> ApiClient {
+        let config = Config {
+            api_key: Some("test-key".to_string()),
+            base_url: Some(server.url("")),
+            timeout: Some(5),
+            ..Default::default()
+        };
+        ApiClient::new(&config).unwrap()
+    }
+
+    fn test_file(dir: &Path) -> (PathBuf, Vec<u8>) {
+        let content: Vec<u8> = (0..FILE_SIZE).map(|i| (i % 251) as u8).collect();
+        let path = dir.join("big.bin");
+        fs::write(&path, &content).unwrap();
+        (path, content)
+    }
+
+    #[test]
+    fn resumes_from_server_offset_after_cut_off() {
+        let dir = temp_dir("resume-cut-off");
+        let (path, content) = test_file(&dir);
+        let checkpoints = UploadCheckpoints::in_dir(dir.join("uploads"));
+        let session = Arc::new(Mutex::new(Session::default()));
+        let server = chunked_server(session.clone(), "up1", Some("bytes 4000"));
+        let client = client(&server);
+        let metadata = UploadMetadata::default();
+
+        let first = client.upload_file_chunked(&checkpoints, THRESHOLD, &path, &metadata);
+        assert!(first.is_err());
+        let checkpoint = checkpoints.find(&path).expect("checkpoint kept");
+        assert_eq!(checkpoint.offset, 4000);
+
+        // The server kept part of the cut off chunk, so the upload goes on from there
+        let second = client.upload_file_chunked(&checkpoints, THRESHOLD, &path, &metadata);
+        assert!(second.is_ok());
+
+        let session = session.lock().unwrap();
+        assert_eq!(session.received, content);
+        assert_eq!(
+            session.ranges,
+            [
+                "bytes 0-3999/10000",
+                "bytes 5500-9499/10000",
+                "bytes 9500-9999/10000"
+            ]
+        );
+        assert!(checkpoints.pending().is_empty());
+    }
+
+    #[test]
+    fn restarts_when_server_forgot_session() {
+        let dir = temp_dir("resume-not-found");
+        let (path, content) = test_file(&dir);
+        let checkpoints = UploadCheckpoints::in_dir(dir.join("uploads"));
+        let mut stale = UploadCheckpoint::new(
+            "stale".to_string(),
+            path.clone(),
+            FILE_SIZE as u64,
+            4000,
+            UploadMetadata::default(),
+        );
+        stale.offset = 4000;
+        checkpoints.save(&stale).unwrap();
+
+        let session = Arc::new(Mutex::new(Session::default()));
+        let server = chunked_server(session.clone(), "fresh", None);
+        let client = client(&server);
+
+        let result =
+            client.upload_file_chunked(&checkpoints, THRESHOLD, &path, &UploadMetadata::default());
+        assert!(result.is_ok());
+
+        let session = session.lock().unwrap();
+        assert_eq!(
+            &session.requests[..2],
+            [
+                "GET /file/upload/chunked/stale",
+                "POST /file/upload/chunked"
+            ]
+        );
+        assert_eq!(session.ranges[0], "bytes 0-3999/10000");
+        assert_eq!(session.received, content);
+        assert!(checkpoints.pending().is_empty());
+    }
+
+    #[test]
+    fn falls_back_to_single_upload_without_chunked_endpoint() {
+        for status in [404, 405, 501] {
+            let dir = temp_dir(&format!("fallback-{}", status));
+            let (path, content) = test_file(&dir);
+            let checkpoints = UploadCheckpoints::in_dir(dir.join("uploads"));
+            let requests = Arc::new(Mutex::new(Vec::new()));
+
+            let seen = requests.clone();
+            let server = StubServer::start(move |request: &StubRequest| {
+                seen.lock().unwrap().push(request.clone());
+                match request.path.as_str() {
+                    "/file/upload/chunked" => Some(StubResponse::new(status)),
+                    "/file/upload" => Some(StubResponse::json(200, UPLOADED)),
+                    _ => Some(StubResponse::new(404)),
+                }
+            });
+            let client = client(&server);
+
+            let result = client.upload_file_chunked(
+                &checkpoints,
+                THRESHOLD,
+                &path,
+                &UploadMetadata::default(),
+            );
+            assert!(result.is_ok(), "status {}", status);
+
+            let requests = requests.lock().unwrap();
+            let paths: Vec<&str> = requests.iter().map(|r| r.path.as_str()).collect();
+            assert_eq!(paths, ["/file/upload/chunked", "/file/upload"]);
+            let upload = &requests[1];
+            assert!(
+                upload
+                    .header("Content-Type")
+                    .is_some_and(|t| t.starts_with("multipart/form-data"))
+            );
+            assert!(upload.body.windows(content.len()).any(|w| w == content));
+            assert!(checkpoints.pending().is_empty());
+        }
+    }
+}
@@ -1,4 +1,5 @@
 mod client;
 
 pub use client::async_bridge;
+pub use client::ApiClient;
 pub use client::TextType;
@@ -6,6 +6,7 @@ use adw::prelude::*;
 use adw::subclass::prelude::*;
 use gtk::{gdk, gio, glib};
 use std::cell::OnceCell;
+use std::path::PathBuf;
 
 mod imp {
     use super::*;
@@ -22,7 +23,12 @@ mod imp {
         type ParentType = adw::Application;
     }
 
-    impl ObjectImpl for SeeApplication {}
+    impl ObjectImpl for SeeApplication {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().setup_options();
+        }
+    }
 
     impl ApplicationImpl for SeeApplication {
         fn activate(&self) {
@@ -36,6 +42,45 @@ mod imp {
             window.present();
         }
 
+        fn open(&self, files: &[gio::File], _hint: &str) {
+            let mut urls = Vec::new();
+            let mut paths = Vec::new();
+            for file in files {
+                if file.uri_scheme().is_some_and(|s| is_web_scheme(&s)) {
+                    urls.push(file.uri().to_string());
+                } else if let Some(path) = file.path() {
+                    paths.push(path);
+                }
+            }
+            self.obj().open_targets(None, urls, paths, false);
+        }
+
+        fn command_line(&self, command_line: &gio::ApplicationCommandLine) -> glib::ExitCode {
+            let options = command_line.options_dict();
+            let page = ["links", "texts", "files"]
+                .into_iter()
+                .find(|name| options.contains(name));
+            let shorten = options.contains("shorten");
+
+            // Links are kept as typed; anything else is a file relative to the caller's directory
+            let mut urls = Vec::new();
+            let mut paths = Vec::new();
+            for arg in command_line.arguments().iter().skip(1) {
+                let text = arg.to_string_lossy();
+                if is_web_url(&text) {
+                    urls.push(text.into_owned());
+                } else if let Some(path) = command_line.create_file_for_arg(arg).path() {
+                    paths.push(path);
+                }
+            }
+
+            if shorten && urls.is_empty() {
+                log::warn!("--shorten was given without a URL");
+            }
+            self.obj().open_targets(page, urls, paths, shorten);
+            glib::ExitCode::SUCCESS
+        }
+
         fn startup(&self) {
             self.parent_startup();
 
@@ -75,7 +120,10 @@ impl SeeApplication {
     pub fn new() -> Self {
         glib::Object::builder()
             .property("application-id", APP_ID)
-            .property("flags", gio::ApplicationFlags::FLAGS_NONE)
+            .property(
+                "flags",
+                gio::ApplicationFlags::HANDLES_OPEN | gio::ApplicationFlags::HANDLES_COMMAND_LINE,
+            )
             .build()
     }
 
@@ -101,6 +149,67 @@ impl SeeApplication {
         }
     }
 
+    /// Options for the window, parsed in the launching process and passed on to the
+    /// running instance. Subcommands never get here, see `cli`.
+    fn setup_options(&self) {
+        self.set_option_context_parameter_string(Some("[FILE…|URL]"));
+        self.set_option_context_summary(Some(
+            "Files are uploaded and a URL is filled in on the Links tab of the open window.\n\
+             Run \"see help\" for the commands that work without a window.",
+        ));
+
+        let options = [
+            ("links", b'l', "Show the Links tab"),
+            ("texts", b't', "Show the Texts tab"),
+            ("files", b'f', "Show the Files tab"),
+            ("shorten", b's', "Shorten the given URL right away"),
+        ];
+        for (name, short, description) in options {
+            self.add_main_option(
+                name,
+                glib::Char::from(short),
+                glib::OptionFlags::NONE,
+                glib::OptionArg::None,
+                description,
+                None,
+            );
+        }
+    }
+
+    /// Bring up the window and hand it the links and files the app was launched with
+    fn open_targets(
+        &self,
+        page: Option<&str>,
+        urls: Vec<String>,
+        paths: Vec<PathBuf>,
+        shorten: bool,
+    ) {
+        self.activate();
+        let Some(window) = self.active_window().and_downcast::<SeeWindow>() else {
+            return;
+        };
+
+        if let Some(page) = page {
+            window.show_page(page);
+        }
+
+        // The form holds one link, so only a shortened batch goes beyond the first
+        if shorten {
+            for url in &urls {
+                window.open_url(url, true);
+            }
+        } else if let Some(url) = urls.first() {
+            if urls.len() > 1 {
+                log::warn!("Only the first of {} URLs is filled in", urls.len());
+            }
+            window.open_url(url, false);
+        }
+
+        if !paths.is_empty() {
+            window.upload_files(paths);
+        }
+    }
+
     fn setup_gactions(&self) {
         let quit_action = gio::ActionEntry::builder("quit")
             .activate(|app: &Self, _, _| app.quit())
@@ -195,10 +304,25 @@ impl SeeApplication {
         if let Some(win) = window {
             preferences.set_transient_for(Some(&win));
         }
+        preferences.connect_close_request(|preferences| {
+            if let Some(window) = preferences.transient_for().and_downcast::<SeeWindow>() {
+                window.reload_preferences();
+            }
+            glib::Propagation::Proceed
+        });
         preferences.present();
     }
 }
 
+fn is_web_scheme(scheme: &str) -> bool {
+    scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https")
+}
+
+fn is_web_url(arg: &str) -> bool {
+    arg.split_once("://")
+        .is_some_and(|(scheme, rest)| is_web_scheme(scheme) && !rest.is_empty())
+}
+
 impl Default for SeeApplication {
     fn default() -> Self {
         Self::new()
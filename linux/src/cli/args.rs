@@ -0,0 +1,542 @@
+use super::history::{HistoryCommand, HistoryFilter, HistoryFormat, ItemKind};
+use crate::api::TextType;
+use crate::error::{AppError, Result};
+use chrono::{DateTime, Local, NaiveDate, TimeDelta, Utc};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: see [COMMAND] [OPTIONS]
+       see [--links|--texts|--files] [--shorten] [FILE...|URL]
+
+Without a command the app window is opened. Files are uploaded and a URL is
+filled in on the Links tab, or shortened with --shorten.
+
+Commands:
+  shorten <url>       Shorten a URL
+      --domain <domain>   Short link domain
+      --slug <alias>      Custom alias
+      --title <title>     Title kept in history
+      --keep-original     Skip tracking parameter removal and rewrite rules
+  paste               Share text read from standard input
+      --title <title>     Title of the text
+      --type <type>       plain, code or markdown
+      --domain <domain>   Text domain
+  upload <file>...    Upload one or more files
+  delete <url>...     Delete links, texts or files from history and the server
+  history list        List history, newest first
+  history search <text>
+                      List items whose URL, title, name or text contains <text>
+  history show <url>  Show everything recorded about one item
+  history export      Print history as JSON in the history.json layout, or as CSV
+      --output <file>     Write to a file instead
+  history remove [<url>...]
+                      Remove items from history, by URL or by filters
+      --server            Delete them on the server too
+      --dry-run           List what would be removed
+      --yes               Remove by filters without asking
+  help                Show this help
+
+History filters:
+      --kind <kind>       link, text or file
+      --domain <domain>   Only this domain
+      --since <date>      Created on or after a date (2025-01-31) or days ago (30d)
+      --before <date>     Created before a date or days ago
+      --limit <count>     At most this many items (list and search)
+      --format <format>   table, json or csv (export: json or csv)
+
+Every command accepts --json to print results as JSON, one object per line.";
+
+/// A subcommand and its options, as given on the command line
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Shorten {
+        url: String,
+        domain: Option<String>,
+        slug: Option<String>,
+        title: Option<String>,
+        keep_original: bool,
+    },
+    Paste {
+        title: Option<String>,
+        text_type: Option<TextType>,
+        domain: Option<String>,
+    },
+    Upload {
+        files: Vec<PathBuf>,
+    },
+    Delete {
+        urls: Vec<String>,
+    },
+    History(HistoryCommand),
+    Help,
+}
+
+/// A parsed command line
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invocation {
+    pub command: Command,
+    /// Print JSON instead of plain URLs
+    pub json: bool,
+}
+
+const COMMANDS: &[&str] = &["shorten", "paste", "upload", "delete", "history", "help"];
+
+/// Options that narrow down history
+const FILTER_OPTIONS: &[&str] = &["kind", "domain", "since", "before", "limit", "format"];
+
+impl Invocation {
+    /// Whether the arguments name a subcommand, rather than options for the app window
+    pub fn is_command(args: &[String]) -> bool {
+        let args = args.strip_prefix(&["--json".to_string()]).unwrap_or(args);
+        args.first().is_some_and(|a| COMMANDS.contains(&a.as_str()))
+    }
+
+    /// Parse the arguments after the program name
+    pub fn parse(args: &[String]) -> Result<Self> {
+        // `see --json upload a.png` is read as `see upload a.png --json`
+        let mut args = args.to_vec();
+        if args.first().is_some_and(|a| a == "--json") {
+            let json = args.remove(0);
+            let end = args.iter().position(|a| a == "--").unwrap_or(args.len());
+            args.insert(end, json);
+        }
+
+        let Some((name, rest)) = args.split_first() else {
+            return Err(AppError::Usage("no command given".to_string()));
+        };
+
+        let (command, options) = match name.as_str() {
+            "shorten" => {
+                let mut options =
+                    Options::parse(rest, &["domain", "slug", "title"], &["keep-original"])?;
+                let url = options.single_positional("a URL to shorten")?;
+                let command = Command::Shorten {
+                    url,
+                    domain: options.take("domain"),
+                    slug: options.take("slug"),
+                    title: options.take("title"),
+                    keep_original: options.has("keep-original"),
+                };
+                (command, options)
+            }
+            "paste" => {
+                let mut options = Options::parse(rest, &["title", "type", "domain"], &[])?;
+                options.no_positionals()?;
+                let text_type = options
+                    .take("type")
+                    .map(|t| parse_text_type(&t))
+                    .transpose()?;
+                let command = Command::Paste {
+                    title: options.take("title"),
+                    text_type,
+                    domain: options.take("domain"),
+                };
+                (command, options)
+            }
+            "upload" => {
+                let mut options = Options::parse(rest, &[], &[])?;
+                let files = options.positionals("at least one file to upload")?;
+                let command = Command::Upload {
+                    files: files.into_iter().map(PathBuf::from).collect(),
+                };
+                (command, options)
+            }
+            "delete" => {
+                let mut options = Options::parse(rest, &[], &[])?;
+                let urls = options.positionals("at least one URL to delete")?;
+                (Command::Delete { urls }, options)
+            }
+            "history" => {
+                let (command, options) = parse_history(rest)?;
+                (Command::History(command), options)
+            }
+            "help" => (Command::Help, Options::parse(rest, &[], &[])?),
+            other => return Err(AppError::Usage(format!("unknown command \"{}\"", other))),
+        };
+
+        let json = options.has("json");
+        let help = options.has("help");
+        Ok(Self {
+            command: if help { Command::Help } else { command },
+            json,
+        })
+    }
+}
+
+fn parse_history(args: &[String]) -> Result<(HistoryCommand, Options)> {
+    let Some((name, rest)) = args.split_first() else {
+        return Err(AppError::Usage(
+            "expected list, search, show, export or remove after history".to_string(),
+        ));
+    };
+    if name == "--help" {
+        return Ok((
+            HistoryCommand::List {
+                filter: HistoryFilter::default(),
+                format: HistoryFormat::Table,
+            },
+            Options::parse(args, &[], &[])?,
+        ));
+    }
+
+    let command = match name.as_str() {
+        "list" => {
+            let mut options = Options::parse(rest, FILTER_OPTIONS, &[])?;
+            options.no_positionals()?;
+            let command = HistoryCommand::List {
+                filter: options.filter()?,
+                format: options.format(HistoryFormat::Table)?,
+            };
+            (command, options)
+        }
+        "search" => {
+            let mut options = Options::parse(rest, FILTER_OPTIONS, &[])?;
+            let query = options.single_positional("the text to search for")?;
+            let command = HistoryCommand::Search {
+                query,
+                filter: options.filter()?,
+                format: options.format(HistoryFormat::Table)?,
+            };
+            (command, options)
+        }
+        "show" => {
+            let mut options = Options::parse(rest, &["format"], &[])?;
+            let url = options.single_positional("the URL of an item in history")?;
+            let command = HistoryCommand::Show {
+                url,
+                format: options.format(HistoryFormat::Table)?,
+            };
+            (command, options)
+        }
+        "export" => {
+            let mut values = FILTER_OPTIONS.to_vec();
+            values.push("output");
+            let mut options = Options::parse(rest, &values, &[])?;
+            options.no_positionals()?;
+            let format = options.format(HistoryFormat::Json)?;
+            if format == HistoryFormat::Table {
+                return Err(AppError::Usage("export writes json or csv".to_string()));
+            }
+            let command = HistoryCommand::Export {
+                filter: options.filter()?,
+                format,
+                output: options.take("output").map(PathBuf::from),
+            };
+            (command, options)
+        }
+        "remove" => {
+            let mut options = Options::parse(rest, FILTER_OPTIONS, &["server", "yes", "dry-run"])?;
+            let urls = std::mem::take(&mut options.positionals);
+            let filter = options.filter()?;
+            if urls.is_empty() && filter.is_empty() && !options.has("help") {
+                return Err(AppError::Usage(
+                    "give the URLs to remove, or filters like --kind or --before".to_string(),
+                ));
+            }
+            if !urls.is_empty() && !filter.is_empty() {
+                return Err(AppError::Usage(
+                    "remove takes either URLs or filters, not both".to_string(),
+                ));
+            }
+            let command = HistoryCommand::Remove {
+                urls,
+                filter,
+                server: options.has("server"),
+                yes: options.has("yes"),
+                dry_run: options.has("dry-run"),
+                json: options.has("json"),
+            };
+            (command, options)
+        }
+        other => {
+            return Err(AppError::Usage(format!(
+                "unknown history command \"{}\"",
+                other
+            )));
+        }
+    };
+    Ok(command)
+}
+
+/// A date like `2025-01-31`, as the start of that day locally, or a number of days ago like `30d`
+fn parse_date(option: &str, value: &str) -> Result<DateTime<Utc>> {
+    if let Some(days) = value.strip_suffix('d').and_then(|d| d.parse::<i64>().ok()) {
+        return Ok(Utc::now() - TimeDelta::days(days));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .and_then(|time| time.and_local_timezone(Local).earliest())
+        .map(|time| time.with_timezone(&Utc))
+        .ok_or_else(|| {
+            AppError::Usage(format!(
+                "--{} takes a date like 2025-01-31 or a number of days like 30d",
+                option
+            ))
+        })
+}
+
+fn parse_text_type(name: &str) -> Result<TextType> {
+    match name {
+        "plain" | "plain_text" => Ok(TextType::PlainText),
+        "code" | "source_code" => Ok(TextType::SourceCode),
+        "markdown" | "md" => Ok(TextType::Markdown),
+        _ => Err(AppError::Usage(format!(
+            "unknown text type \"{}\", expected plain, code or markdown",
+            name
+        ))),
+    }
+}
+
+/// Options of one subcommand: `--name value`, `--name=value`, switches and positionals
+struct Options {
+    values: HashMap<String, String>,
+    switches: HashSet<String>,
+    positionals: Vec<String>,
+}
+
+impl Options {
+    /// Switches every command understands
+    const COMMON_SWITCHES: &'static [&'static str] = &["json", "help"];
+
+    fn parse(args: &[String], value_names: &[&str], switch_names: &[&str]) -> Result<Self> {
+        let mut options = Self {
+            values: HashMap::new(),
+            switches: HashSet::new(),
+            positionals: Vec::new(),
+        };
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                options.positionals.extend(args.by_ref().cloned());
+                break;
+            }
+            let Some(flag) = arg.strip_prefix("--") else {
+                options.positionals.push(arg.clone());
+                continue;
+            };
+
+            let (name, inline_value) = match flag.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (flag, None),
+            };
+            if value_names.contains(&name) {
+                let value = match inline_value {
+                    Some(value) => value,
+                    None => args
+                        .next()
+                        .cloned()
+                        .ok_or_else(|| AppError::Usage(format!("--{} needs a value", name)))?,
+                };
+                options.values.insert(name.to_string(), value);
+            } else if switch_names.contains(&name) || Self::COMMON_SWITCHES.contains(&name) {
+                if inline_value.is_some() {
+                    return Err(AppError::Usage(format!("--{} does not take a value", name)));
+                }
+                options.switches.insert(name.to_string());
+            } else {
+                return Err(AppError::Usage(format!("unknown option --{}", name)));
+            }
+        }
+        Ok(options)
+    }
+
+    fn take(&mut self, name: &str) -> Option<String> {
+        self.values.remove(name)
+    }
+
+    fn has(&self, name: &str) -> bool {
+        self.switches.contains(name)
+    }
+
+    fn positionals(&mut self, expected: &str) -> Result<Vec<String>> {
+        if self.positionals.is_empty() && !self.has("help") {
+            return Err(AppError::Usage(format!("expected {}", expected)));
+        }
+        Ok(std::mem::take(&mut self.positionals))
+    }
+
+    fn single_positional(&mut self, expected: &str) -> Result<String> {
+        let mut positionals = self.positionals(expected)?;
+        if positionals.len() > 1 {
+            return Err(AppError::Usage(format!("expected only {}", expected)));
+        }
+        Ok(positionals.pop().unwrap_or_default())
+    }
+
+    fn filter(&mut self) -> Result<HistoryFilter> {
+        let kind = self
+            .take("kind")
+            .map(|k| {
+                ItemKind::parse(&k).ok_or_else(|| {
+                    AppError::Usage(format!(
+                        "unknown kind \"{}\", expected link, text or file",
+                        k
+                    ))
+                })
+            })
+            .transpose()?;
+        let since = self
+            .take("since")
+            .map(|d| parse_date("since", &d))
+            .transpose()?;
+        let before = self
+            .take("before")
+            .map(|d| parse_date("before", &d))
+            .transpose()?;
+        let limit = self
+            .take("limit")
+            .map(|l| {
+                l.parse()
+                    .map_err(|_| AppError::Usage("--limit takes a number".to_string()))
+            })
+            .transpose()?;
+
+        Ok(HistoryFilter {
+            kind,
+            domain: self.take("domain"),
+            since,
+            before,
+            limit,
+        })
+    }
+
+    /// `--format`, where `--json` is short for `--format json`
+    fn format(&mut self, default: HistoryFormat) -> Result<HistoryFormat> {
+        if self.has("json") {
+            return Ok(HistoryFormat::Json);
+        }
+        match self.take("format") {
+            Some(name) => HistoryFormat::parse(&name).ok_or_else(|| {
+                AppError::Usage(format!(
+                    "unknown format \"{}\", expected table, json or csv",
+                    name
+                ))
+            }),
+            None => Ok(default),
+        }
+    }
+
+    fn no_positionals(&self) -> Result<()> {
+        match self.positionals.first() {
+            Some(arg) => Err(AppError::Usage(format!("unexpected argument \"{}\"", arg))),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    fn parse(line: &str) -> Result<Invocation> {
+        Invocation::parse(&args(line))
+    }
+
+    fn usage_error(line: &str) -> String {
+        match parse(line) {
+            Err(AppError::Usage(message)) => message,
+            other => panic!("{:?} gave {:?}", line, other.map(|i| i.command)),
+        }
+    }
+
+    #[test]
+    fn takes_values_inline_or_separate() {
+        let inline = parse("shorten example.com --domain=s.ee --slug=launch").unwrap();
+        let separate = parse("shorten --domain s.ee example.com --slug launch").unwrap();
+        assert_eq!(inline, separate);
+        assert_eq!(
+            inline.command,
+            Command::Shorten {
+                url: "example.com".to_string(),
+                domain: Some("s.ee".to_string()),
+                slug: Some("launch".to_string()),
+                title: None,
+                keep_original: false,
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_a_flag_without_its_value() {
+        assert!(usage_error("shorten example.com --domain").contains("--domain needs a value"));
+    }
+
+    #[test]
+    fn rejects_unknown_flags() {
+        assert!(usage_error("shorten example.com --bogus").contains("unknown option --bogus"));
+        assert!(usage_error("upload a.png --domain s.ee").contains("unknown option --domain"));
+        assert!(usage_error("shorten example.com --json=yes").contains("does not take a value"));
+    }
+
+    #[test]
+    fn double_dash_ends_options() {
+        let invocation = parse("upload -- --json -x.png").unwrap();
+        assert!(!invocation.json);
+        assert_eq!(
+            invocation.command,
+            Command::Upload {
+                files: vec![PathBuf::from("--json"), PathBuf::from("-x.png")],
+            }
+        );
+
+        let invocation = parse("--json upload -- --x.png").unwrap();
+        assert!(invocation.json);
+        assert_eq!(
+            invocation.command,
+            Command::Upload {
+                files: vec![PathBuf::from("--x.png")],
+            }
+        );
+    }
+
+    #[test]
+    fn accepts_json_before_or_after_the_command() {
+        for line in [
+            "--json shorten example.com",
+            "shorten --json example.com",
+            "shorten example.com --json",
+        ] {
+            let invocation = parse(line).unwrap();
+            assert!(invocation.json, "for {:?}", line);
+            assert!(matches!(invocation.command, Command::Shorten { .. }));
+        }
+        assert!(!parse("shorten example.com").unwrap().json);
+        assert_eq!(
+            parse("--json history list").unwrap().command,
+            Command::History(HistoryCommand::List {
+                filter: HistoryFilter::default(),
+                format: HistoryFormat::Json,
+            })
+        );
+    }
+
+    #[test]
+    fn only_command_names_are_commands() {
+        for line in [
+            "shorten example.com",
+            "--json upload a.png",
+            "history list",
+            "help",
+        ] {
+            assert!(Invocation::is_command(&args(line)), "for {:?}", line);
+        }
+        for line in [
+            "",
+            "--json",
+            "./shorten",
+            "shorten.txt",
+            "https://s.ee/shorten",
+            "--shorten example.com",
+            "photo.png upload",
+        ] {
+            assert!(!Invocation::is_command(&args(line)), "for {:?}", line);
+        }
+    }
+}
@@ -0,0 +1,335 @@
+use super::args::{Command, Invocation, USAGE};
+use super::history::{self, HistoryItem};
+use crate::api::{ApiClient, TextType};
+use crate::config::Config;
+use crate::error::{AppError, Result};
+use crate::links::{DuplicateLinkPolicy, UrlNormalizer, UrlRewriter};
+use crate::media::{FileInspector, ImageOptions, ImageProcessor, UploadLimits, staging};
+use crate::storage::{
+    FileEntry, HistoryStorage, LinkEntry, TextEntry, UploadCheckpoints, UploadMetadata,
+};
+use crate::text::TextLoader;
+use serde::Serialize;
+use std::io::{IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Run a subcommand against the saved config and history, without any UI
+pub fn run(invocation: Invocation) -> Result<()> {
+    let output = Output {
+        json: invocation.json,
+    };
+
+    match invocation.command {
+        Command::Shorten {
+            url,
+            domain,
+            slug,
+            title,
+            keep_original,
+        } => shorten(&output, &url, domain, slug, title, keep_original),
+        Command::Paste {
+            title,
+            text_type,
+            domain,
+        } => paste(&output, title, text_type, domain),
+        Command::Upload { files } => upload(&output, &files),
+        Command::Delete { urls } => delete(&output, &urls),
+        Command::History(command) => history::run(command),
+        Command::Help => {
+            writeln!(std::io::stdout(), "{}", USAGE)?;
+            Ok(())
+        }
+    }
+}
+
+/// Prints each result as a plain URL, or as one line of JSON
+struct Output {
+    json: bool,
+}
+
+impl Output {
+    /// Write one result. A closed pipe is reported as an error instead of a panic.
+    fn print<T: Serialize>(&self, url: &str, value: &T) -> Result<()> {
+        let mut stdout = std::io::stdout();
+        if self.json {
+            writeln!(stdout, "{}", serde_json::to_string(value)?)?;
+        } else {
+            writeln!(stdout, "{}", url)?;
+        }
+        Ok(())
+    }
+}
+
+/// A history entry that was removed, as printed by `delete`
+#[derive(Serialize)]
+struct Deleted<'a> {
+    kind: &'static str,
+    url: &'a str,
+}
+
+fn shorten(
+    output: &Output,
+    input: &str,
+    domain: Option<String>,
+    slug: Option<String>,
+    title: Option<String>,
+    keep_original: bool,
+) -> Result<()> {
+    let config = Config::load()?;
+    let mut storage = HistoryStorage::load()?;
+
+    let url = UrlNormalizer::normalize(input)?;
+    let url = if keep_original {
+        url
+    } else {
+        UrlRewriter::from_config(&config).rewrite(&url)
+    };
+    let url = url.to_string();
+    let domain = domain
+        .or_else(|| config.default_link_domain().map(String::from))
+        .unwrap_or_else(|| "s.ee".to_string());
+    let slug = slug.filter(|s| !s.is_empty());
+
+    // An alias asks for a particular link, so only that one counts as the same
+    let existing = storage
+        .find_link(&url, &domain)
+        .filter(|e| slug.as_ref().is_none_or(|s| *s == e.slug));
+    let reuse = config.duplicate_links().unattended() == DuplicateLinkPolicy::Reuse;
+    if let Some(existing) = existing.filter(|_| reuse) {
+        eprintln!("Already shortened on {}, reusing the existing link", domain);
+        return output.print(&existing.short_url, existing);
+    }
+
+    let client = ApiClient::new(&config)?;
+    let result = client.shorten_url(&url, Some(&domain), slug.as_deref())?;
+    let title = title
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty());
+    let entry = LinkEntry::new(
+        url,
+        result.data.short_url.clone(),
+        domain,
+        result.data.slug.clone(),
+        title,
+    );
+    storage.add_link(entry.clone())?;
+
+    output.print(&entry.short_url, &entry)
+}
+
+fn paste(
+    output: &Output,
+    title: Option<String>,
+    text_type: Option<TextType>,
+    domain: Option<String>,
+) -> Result<()> {
+    let config = Config::load()?;
+    let mut storage = HistoryStorage::load()?;
+
+    let mut stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        return Err(AppError::Usage(
+            "pipe or redirect the text to paste, as in: see paste < notes.txt".to_string(),
+        ));
+    }
+    let mut bytes = Vec::new();
+    stdin.read_to_end(&mut bytes)?;
+    let (content, _) = TextLoader::decode(&bytes)
+        .ok_or_else(|| AppError::UnsupportedFile("the input is not text".to_string()))?;
+    if content.trim().is_empty() {
+        return Err(AppError::Usage("there is no text to paste".to_string()));
+    }
+
+    let title = title
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| "Untitled".to_string());
+    let domain = domain.or_else(|| config.default_text_domain().map(String::from));
+
+    let client = ApiClient::new(&config)?;
+    let result = client.create_text(&content, &title, domain.as_deref(), text_type, None)?;
+
+    // Same shape as the entries the Texts view records
+    let short_url = result.data.short_url.clone();
+    let domain = short_url.split('/').nth(2).unwrap_or("p.s.ee").to_string();
+    let page_url = format!("https://{}/{}", domain, result.data.slug);
+    let entry = TextEntry::new(
+        short_url,
+        Some(page_url),
+        domain,
+        result.data.slug.clone(),
+        Some(title),
+        None,
+        content.chars().take(100).collect(),
+    );
+    storage.add_text(entry.clone())?;
+
+    output.print(&entry.url, &entry)
+}
+
+/// Upload every file, carrying on past failures and reporting them at the end
+fn upload(output: &Output, files: &[PathBuf]) -> Result<()> {
+    let config = Config::load()?;
+    let mut storage = HistoryStorage::load()?;
+    let client = ApiClient::new(&config)?;
+
+    let mut failed = 0;
+    for path in files {
+        match upload_file(&client, &config, path) {
+            Ok(entry) => {
+                storage.add_file(entry.clone())?;
+                output.print(&entry.url, &entry)?;
+            }
+            Err(e) => {
+                eprintln!("see: {}: {}", path.display(), e);
+                failed += 1;
+            }
+        }
+    }
+
+    match failed {
+        0 => Ok(()),
+        n => Err(AppError::Api(format!(
+            "{} of {} uploads failed",
+            n,
+            files.len()
+        ))),
+    }
+}
+
+/// Check, prepare and send one file the way the Files view does
+fn upload_file(client: &ApiClient, config: &Config, path: &Path) -> Result<FileEntry> {
+    if !path.is_file() {
+        return Err(AppError::UnsupportedFile("not a file".to_string()));
+    }
+
+    let mut info = UploadMetadata::default();
+    let mut upload_path = path.to_path_buf();
+    let processed = if config.process_dropped_images() {
+        ImageProcessor::process_file(path, &ImageOptions::from_config(config))?
+    } else {
+        None
+    };
+    if let Some(processed) = processed {
+        // Re-encoding never carries the original metadata over
+        upload_path = processed;
+        info.staged = true;
+        info.metadata_stripped = true;
+    }
+
+    let stripped = if config.strip_image_metadata() && !info.metadata_stripped {
+        ImageProcessor::strip_metadata(path)?
+    } else {
+        None
+    };
+    if let Some(stripped) = stripped {
+        upload_path = stripped;
+        info.staged = true;
+        info.metadata_stripped = true;
+    }
+
+    let result = send_file(client, config, &upload_path, &mut info);
+
+    // Keep the staged copy around while a chunked upload can still resume
+    let resumable = UploadCheckpoints::load()
+        .ok()
+        .and_then(|c| c.find(&upload_path))
+        .is_some();
+    if info.staged && !resumable {
+        staging::discard(&upload_path);
+    }
+
+    let result = match result {
+        Ok(result) => result,
+        Err(e) if resumable => {
+            return Err(AppError::Api(format!(
+                "upload interrupted ({}), it will resume the next time the app is opened",
+                e
+            )));
+        }
+        Err(e) => return Err(e),
+    };
+
+    let domain = result
+        .data
+        .url
+        .split('/')
+        .nth(2)
+        .unwrap_or("i.s.ee")
+        .to_string();
+    let mut entry = FileEntry::new(
+        result.data.url.clone(),
+        result.data.page.clone(),
+        domain,
+        result.data.hash.clone(),
+        result.data.filename.clone(),
+        result.data.size,
+        info.file_info.mime_type,
+    );
+    entry.width = info.file_info.width;
+    entry.height = info.file_info.height;
+    entry.page_count = info.file_info.page_count;
+    entry.sha256 = info.file_info.sha256;
+    entry.metadata_stripped = info.metadata_stripped;
+    Ok(entry)
+}
+
+fn send_file(
+    client: &ApiClient,
+    config: &Config,
+    path: &Path,
+    info: &mut UploadMetadata,
+) -> Result<see_sdk::file::models::FileUploadResponse> {
+    match FileInspector::inspect(path) {
+        Ok(file_info) => info.file_info = file_info,
+        Err(e) => log::warn!("Failed to inspect file: {}", e),
+    }
+
+    let filename = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let size = std::fs::metadata(path)?.len();
+    UploadLimits::from_config(config).check(
+        &filename,
+        size,
+        info.file_info.mime_type.as_deref(),
+    )?;
+
+    client.upload_file_resumable(path, info)
+}
+
+/// Delete items from the server and history, looked up by any URL they were shared under
+fn delete(output: &Output, urls: &[String]) -> Result<()> {
+    let config = Config::load()?;
+    let mut storage = HistoryStorage::load()?;
+    let client = ApiClient::new(&config)?;
+
+    let mut failed = 0;
+    for url in urls {
+        let result = delete_one(&client, &mut storage, url);
+        match result {
+            Ok(kind) => output.print(url, &Deleted { kind, url })?,
+            Err(e) => {
+                eprintln!("see: {}: {}", url, e);
+                failed += 1;
+            }
+        }
+    }
+
+    match failed {
+        0 => Ok(()),
+        n => Err(AppError::Api(format!(
+            "{} of {} deletions failed",
+            n,
+            urls.len()
+        ))),
+    }
+}
+
+fn delete_one(client: &ApiClient, storage: &mut HistoryStorage, url: &str) -> Result<&'static str> {
+    let item = HistoryItem::find(storage, url)
+        .ok_or_else(|| AppError::Storage("not found in history".to_string()))?;
+    item.delete_on_server(client)?;
+    item.remove_from(storage)?;
+    Ok(item.kind_name())
+}
@@ -0,0 +1,620 @@
+use crate::api::ApiClient;
+use crate::config::Config;
+use crate::error::{AppError, Result};
+use crate::storage::{FileEntry, HistoryStorage, LinkEntry, TextEntry};
+use chrono::{DateTime, Local, Utc};
+use serde::Serialize;
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+
+/// Which part of history an item belongs to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ItemKind {
+    Link,
+    Text,
+    File,
+}
+
+impl ItemKind {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "link" | "links" => Some(ItemKind::Link),
+            "text" | "texts" => Some(ItemKind::Text),
+            "file" | "files" => Some(ItemKind::File),
+            _ => None,
+        }
+    }
+}
+
+/// How listed items are printed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HistoryFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+impl HistoryFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "table" => Some(HistoryFormat::Table),
+            "json" => Some(HistoryFormat::Json),
+            "csv" => Some(HistoryFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// Narrows history down by kind, domain and creation time
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HistoryFilter {
+    pub kind: Option<ItemKind>,
+    pub domain: Option<String>,
+    /// Created at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Created before this time
+    pub before: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+}
+
+impl HistoryFilter {
+    /// Whether anything other than the limit was given
+    pub fn is_empty(&self) -> bool {
+        self.kind.is_none()
+            && self.domain.is_none()
+            && self.since.is_none()
+            && self.before.is_none()
+    }
+
+    fn matches(&self, item: &HistoryItem) -> bool {
+        let created_at = item.created_at();
+        self.kind.is_none_or(|k| k == item.kind())
+            && self
+                .domain
+                .as_ref()
+                .is_none_or(|d| d.eq_ignore_ascii_case(item.domain()))
+            && self.since.is_none_or(|t| created_at >= t)
+            && self.before.is_none_or(|t| created_at < t)
+    }
+
+    /// Matching items from history, newest first
+    fn apply(&self, storage: &HistoryStorage) -> Vec<HistoryItem> {
+        let items = HistoryItem::all(storage)
+            .into_iter()
+            .filter(|item| self.matches(item));
+        match self.limit {
+            Some(limit) => items.take(limit).collect(),
+            None => items.collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HistoryCommand {
+    List {
+        filter: HistoryFilter,
+        format: HistoryFormat,
+    },
+    Search {
+        query: String,
+        filter: HistoryFilter,
+        format: HistoryFormat,
+    },
+    Show {
+        url: String,
+        format: HistoryFormat,
+    },
+    Export {
+        filter: HistoryFilter,
+        format: HistoryFormat,
+        output: Option<PathBuf>,
+    },
+    Remove {
+        urls: Vec<String>,
+        filter: HistoryFilter,
+        /// Delete on the server as well, like deleting from the app does
+        server: bool,
+        /// Remove by filters without asking first
+        yes: bool,
+        /// Only list what would be removed
+        dry_run: bool,
+        /// Print each removed item as a line of JSON
+        json: bool,
+    },
+}
+
+/// One link, text or file from history
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HistoryItem {
+    Link(LinkEntry),
+    Text(TextEntry),
+    File(FileEntry),
+}
+
+impl HistoryItem {
+    /// Everything in history, newest first
+    pub fn all(storage: &HistoryStorage) -> Vec<Self> {
+        let mut items: Vec<Self> = storage
+            .links()
+            .iter()
+            .cloned()
+            .map(HistoryItem::Link)
+            .chain(storage.texts().iter().cloned().map(HistoryItem::Text))
+            .chain(storage.files().iter().cloned().map(HistoryItem::File))
+            .collect();
+        items.sort_by_key(|item| std::cmp::Reverse(item.created_at()));
+        items
+    }
+
+    /// The item shared under a URL, whether its short, share page or direct URL
+    pub fn find(storage: &HistoryStorage, url: &str) -> Option<Self> {
+        let url = url.trim();
+        Self::all(storage)
+            .into_iter()
+            .find(|item| item.urls().iter().any(|u| u == url))
+    }
+
+    pub fn kind(&self) -> ItemKind {
+        match self {
+            HistoryItem::Link(_) => ItemKind::Link,
+            HistoryItem::Text(_) => ItemKind::Text,
+            HistoryItem::File(_) => ItemKind::File,
+        }
+    }
+
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            HistoryItem::Link(_) => "link",
+            HistoryItem::Text(_) => "text",
+            HistoryItem::File(_) => "file",
+        }
+    }
+
+    /// The URL to share, with the key in the fragment for encrypted items
+    pub fn url(&self) -> String {
+        match self {
+            HistoryItem::Link(entry) => entry.short_url.clone(),
+            HistoryItem::Text(entry) => entry.share_url(),
+            HistoryItem::File(entry) => entry.share_url(),
+        }
+    }
+
+    fn urls(&self) -> Vec<String> {
+        let mut urls = vec![self.url()];
+        match self {
+            HistoryItem::Link(_) => {}
+            HistoryItem::Text(entry) => {
+                urls.push(entry.url.clone());
+                urls.extend(entry.page_url.clone());
+            }
+            HistoryItem::File(entry) => {
+                urls.push(entry.url.clone());
+                urls.extend(entry.page_url.clone());
+            }
+        }
+        urls
+    }
+
+    pub fn domain(&self) -> &str {
+        match self {
+            HistoryItem::Link(entry) => &entry.domain,
+            HistoryItem::Text(entry) => &entry.domain,
+            HistoryItem::File(entry) => &entry.domain,
+        }
+    }
+
+    pub fn slug(&self) -> &str {
+        match self {
+            HistoryItem::Link(entry) => &entry.slug,
+            HistoryItem::Text(entry) => &entry.slug,
+            HistoryItem::File(entry) => &entry.slug,
+        }
+    }
+
+    pub fn created_at(&self) -> DateTime<Utc> {
+        match self {
+            HistoryItem::Link(entry) => entry.created_at,
+            HistoryItem::Text(entry) => entry.created_at,
+            HistoryItem::File(entry) => entry.created_at,
+        }
+    }
+
+    /// Title of a link or text, or the name of a file
+    fn title(&self) -> Option<&str> {
+        match self {
+            HistoryItem::Link(entry) => entry.title.as_deref(),
+            HistoryItem::Text(entry) => entry.title.as_deref(),
+            HistoryItem::File(entry) => Some(&entry.filename),
+        }
+    }
+
+    /// Short description for the table: destination, title or file name
+    fn summary(&self) -> String {
+        match self {
+            HistoryItem::Link(entry) => match entry.title {
+                Some(ref title) => format!("{} ({})", title, entry.original_url),
+                None => entry.original_url.clone(),
+            },
+            HistoryItem::Text(entry) => entry.title.clone().unwrap_or_default(),
+            HistoryItem::File(entry) => entry.filename.clone(),
+        }
+    }
+
+    /// Case-insensitive match against the URLs, titles, names and text previews
+    fn matches_query(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        let mut fields = self.urls();
+        fields.extend(self.title().map(String::from));
+        match self {
+            HistoryItem::Link(entry) => {
+                fields.push(entry.original_url.clone());
+                fields.extend(entry.campaign.clone());
+            }
+            HistoryItem::Text(entry) => fields.push(entry.content_preview.clone()),
+            HistoryItem::File(entry) => fields.extend(entry.mime_type.clone()),
+        }
+        fields.iter().any(|f| f.to_lowercase().contains(&query))
+    }
+
+    /// Labelled details for `show`
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = vec![("Kind", self.kind_name().to_string()), ("URL", self.url())];
+        match self {
+            HistoryItem::Link(entry) => {
+                fields.push(("Destination", entry.original_url.clone()));
+                fields.extend(entry.title.clone().map(|t| ("Title", t)));
+                fields.extend(entry.campaign.clone().map(|c| ("Campaign", c)));
+            }
+            HistoryItem::Text(entry) => {
+                fields.extend(entry.title.clone().map(|t| ("Title", t)));
+                fields.push(("Raw URL", entry.url.clone()));
+                fields.extend(entry.syntax.clone().map(|s| ("Syntax", s)));
+                if entry.encryption_key.is_some() {
+                    fields.push(("Encrypted", "yes".to_string()));
+                }
+                fields.push(("Preview", entry.content_preview.clone()));
+            }
+            HistoryItem::File(entry) => {
+                fields.push(("File", entry.filename.clone()));
+                fields.push(("Direct URL", entry.url.clone()));
+                fields.extend(entry.page_url.clone().map(|p| ("Share Page", p)));
+                fields.push(("Size", format!("{} bytes", entry.size)));
+                fields.extend(entry.mime_type.clone().map(|m| ("Type", m)));
+                if let (Some(width), Some(height)) = (entry.width, entry.height) {
+                    fields.push(("Dimensions", format!("{} × {}", width, height)));
+                }
+                fields.extend(entry.page_count.map(|p| ("Pages", p.to_string())));
+                fields.extend(entry.sha256.clone().map(|s| ("SHA-256", s)));
+                if entry.metadata_stripped {
+                    fields.push(("Metadata", "removed".to_string()));
+                }
+                if entry.encryption_key.is_some() {
+                    fields.push(("Encrypted", "yes".to_string()));
+                }
+            }
+        }
+        fields.push(("Domain", self.domain().to_string()));
+        fields.push(("Slug", self.slug().to_string()));
+        fields.push(("Created", local_time(self.created_at())));
+        fields
+    }
+
+    /// Delete the item on the server, the way the delete actions in the app do
+    pub fn delete_on_server(&self, client: &ApiClient) -> Result<()> {
+        match self {
+            HistoryItem::Link(entry) => client.delete_url(&entry.domain, &entry.slug),
+            HistoryItem::Text(entry) => client.delete_text(&entry.domain, &entry.slug),
+            HistoryItem::File(entry) => client.delete_file(&entry.slug),
+        }
+    }
+
+    pub fn remove_from(&self, storage: &mut HistoryStorage) -> Result<()> {
+        match self {
+            HistoryItem::Link(entry) => storage.remove_link(&entry.domain, &entry.slug),
+            HistoryItem::Text(entry) => storage.remove_text(&entry.domain, &entry.slug),
+            HistoryItem::File(entry) => storage.remove_file(&entry.domain, &entry.slug),
+        }
+    }
+}
+
+/// An item that was removed, as printed by `remove --json`
+#[derive(Serialize)]
+struct Removed<'a> {
+    kind: &'static str,
+    url: &'a str,
+}
+
+/// History in the same shape as `history.json`
+#[derive(Default, Serialize)]
+struct Export<'a> {
+    links: Vec<&'a LinkEntry>,
+    texts: Vec<&'a TextEntry>,
+    files: Vec<&'a FileEntry>,
+}
+
+pub fn run(command: HistoryCommand) -> Result<()> {
+    let mut storage = HistoryStorage::load()?;
+    let mut stdout = std::io::stdout();
+
+    match command {
+        HistoryCommand::List { filter, format } => {
+            print_items(&mut stdout, &filter.apply(&storage), format)
+        }
+        HistoryCommand::Search {
+            query,
+            filter,
+            format,
+        } => {
+            let limit = filter.limit;
+            let filter = HistoryFilter {
+                limit: None,
+                ..filter
+            };
+            let items: Vec<HistoryItem> = filter
+                .apply(&storage)
+                .into_iter()
+                .filter(|item| item.matches_query(&query))
+                .take(limit.unwrap_or(usize::MAX))
+                .collect();
+            print_items(&mut stdout, &items, format)
+        }
+        HistoryCommand::Show { url, format } => {
+            let item = HistoryItem::find(&storage, &url)
+                .ok_or_else(|| AppError::Storage(format!("{} is not in the history", url)))?;
+            if format == HistoryFormat::Json {
+                writeln!(stdout, "{}", serde_json::to_string_pretty(&item)?)?;
+            } else {
+                let fields = item.fields();
+                let width = fields.iter().map(|(l, _)| l.len()).max().unwrap_or(0);
+                for (label, value) in fields {
+                    writeln!(stdout, "{:width$}  {}", label, value, width = width)?;
+                }
+            }
+            Ok(())
+        }
+        HistoryCommand::Export {
+            filter,
+            format,
+            output,
+        } => {
+            let items = filter.apply(&storage);
+            let mut out: Box<dyn Write> = match output {
+                Some(ref path) => Box::new(std::fs::File::create(path)?),
+                None => Box::new(stdout),
+            };
+            if format == HistoryFormat::Csv {
+                write_csv(&mut out, &items)?;
+            } else {
+                let mut export = Export::default();
+                for item in &items {
+                    match item {
+                        HistoryItem::Link(entry) => export.links.push(entry),
+                        HistoryItem::Text(entry) => export.texts.push(entry),
+                        HistoryItem::File(entry) => export.files.push(entry),
+                    }
+                }
+                writeln!(out, "{}", serde_json::to_string_pretty(&export)?)?;
+            }
+            if let Some(path) = output {
+                eprintln!("Exported {} items to {}", items.len(), path.display());
+            }
+            Ok(())
+        }
+        HistoryCommand::Remove {
+            urls,
+            filter,
+            server,
+            yes,
+            dry_run,
+            json,
+        } => {
+            let mut failed = 0;
+            let mut items = Vec::new();
+            for url in &urls {
+                match HistoryItem::find(&storage, url) {
+                    Some(item) => items.push(item),
+                    None => {
+                        eprintln!("see: {}: not found in history", url);
+                        failed += 1;
+                    }
+                }
+            }
+            if urls.is_empty() {
+                items = filter.apply(&storage);
+            }
+
+            if dry_run {
+                let format = if json {
+                    HistoryFormat::Json
+                } else {
+                    HistoryFormat::Table
+                };
+                print_items(&mut stdout, &items, format)?;
+                return match failed {
+                    0 => Ok(()),
+                    n => Err(AppError::Storage(format!(
+                        "{} of {} URLs are not in the history",
+                        n,
+                        urls.len()
+                    ))),
+                };
+            }
+            if urls.is_empty() && !yes && !items.is_empty() && !confirm(&items, server)? {
+                eprintln!("Nothing removed");
+                return Ok(());
+            }
+
+            let client = if server {
+                Some(ApiClient::new(&Config::load()?)?)
+            } else {
+                None
+            };
+            for item in &items {
+                // Keep the local entry when the server still has the item
+                let deleted = client.as_ref().map_or(Ok(()), |c| item.delete_on_server(c));
+                match deleted.and_then(|()| item.remove_from(&mut storage)) {
+                    Ok(()) if json => {
+                        let url = item.url();
+                        let removed = Removed {
+                            kind: item.kind_name(),
+                            url: &url,
+                        };
+                        writeln!(stdout, "{}", serde_json::to_string(&removed)?)?;
+                    }
+                    Ok(()) => writeln!(stdout, "Removed {} {}", item.kind_name(), item.url())?,
+                    Err(e) => {
+                        eprintln!("see: {}: {}", item.url(), e);
+                        failed += 1;
+                    }
+                }
+            }
+
+            let total = if urls.is_empty() {
+                items.len()
+            } else {
+                urls.len()
+            };
+            match failed {
+                0 => Ok(()),
+                n => Err(AppError::Storage(format!(
+                    "{} of {} removals failed",
+                    n, total
+                ))),
+            }
+        }
+    }
+}
+
+/// Ask before removing items picked by filters, which can match more than expected
+fn confirm(items: &[HistoryItem], server: bool) -> Result<bool> {
+    let stdin = std::io::stdin();
+    if !stdin.is_terminal() {
+        return Err(AppError::Usage(
+            "removing by filters needs --yes, or --dry-run to list what would be removed"
+                .to_string(),
+        ));
+    }
+
+    let mut stderr = std::io::stderr();
+    write_table(&mut stderr, items)?;
+    let place = if server {
+        "history and the server"
+    } else {
+        "history"
+    };
+    write!(
+        stderr,
+        "Remove {} items from {}? [y/N] ",
+        items.len(),
+        place
+    )?;
+    stderr.flush()?;
+
+    let mut answer = String::new();
+    stdin.read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+fn print_items(out: &mut impl Write, items: &[HistoryItem], format: HistoryFormat) -> Result<()> {
+    match format {
+        HistoryFormat::Table => write_table(out, items),
+        HistoryFormat::Json => {
+            for item in items {
+                writeln!(out, "{}", serde_json::to_string(item)?)?;
+            }
+            Ok(())
+        }
+        HistoryFormat::Csv => write_csv(out, items),
+    }
+}
+
+fn write_table(out: &mut impl Write, items: &[HistoryItem]) -> Result<()> {
+    let rows: Vec<[String; 4]> = items
+        .iter()
+        .map(|item| {
+            [
+                item.kind_name().to_string(),
+                local_time(item.created_at()),
+                item.url(),
+                item.summary(),
+            ]
+        })
+        .collect();
+    let header = ["KIND", "CREATED", "URL", "DETAILS"].map(String::from);
+
+    let mut widths = [0; 3];
+    for row in std::iter::once(&header).chain(&rows) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    for row in std::iter::once(&header).chain(&rows) {
+        writeln!(
+            out,
+            "{:w0$}  {:w1$}  {:w2$}  {}",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2]
+        )?;
+    }
+    Ok(())
+}
+
+fn write_csv(out: &mut impl Write, items: &[HistoryItem]) -> Result<()> {
+    writeln!(
+        out,
+        "kind,created_at,url,domain,slug,title,original_url,filename,size,mime_type"
+    )?;
+    for item in items {
+        let (original_url, filename, size, mime_type) = match item {
+            HistoryItem::Link(entry) => (Some(entry.original_url.as_str()), None, None, None),
+            HistoryItem::Text(_) => (None, None, None, None),
+            HistoryItem::File(entry) => (
+                None,
+                Some(entry.filename.as_str()),
+                Some(entry.size.to_string()),
+                entry.mime_type.as_deref(),
+            ),
+        };
+        let title = match item {
+            HistoryItem::File(_) => None,
+            _ => item.title(),
+        };
+        let fields = [
+            item.kind_name(),
+            &item.created_at().to_rfc3339(),
+            &item.url(),
+            item.domain(),
+            item.slug(),
+            title.unwrap_or_default(),
+            original_url.unwrap_or_default(),
+            filename.unwrap_or_default(),
+            size.as_deref().unwrap_or_default(),
+            mime_type.unwrap_or_default(),
+        ];
+        let line: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        writeln!(out, "{}", line.join(","))?;
+    }
+    Ok(())
+}
+
+/// Quote a CSV field when it holds a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn local_time(time: DateTime<Utc>) -> String {
+    time.with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
@@ -0,0 +1,6 @@
+mod args;
+mod commands;
+mod history;
+
+pub use args::Invocation;
+pub use commands::run;
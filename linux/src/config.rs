@@ -1,4 +1,7 @@
 use crate::error::{AppError, Result};
+use crate::format::LinkFormat;
+use crate::links::{DuplicateLinkPolicy, RewriteRule};
+use crate::media::ImageOutputFormat;
 use directories::BaseDirs;
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -18,6 +21,38 @@ pub struct Config {
     pub default_text_domain: Option<String>,
     #[serde(default)]
     pub default_file_domain: Option<String>,
+    #[serde(default)]
+    pub default_copy_format: Option<LinkFormat>,
+    #[serde(default)]
+    pub file_gallery: Option<bool>,
+    #[serde(default)]
+    pub markdown_preview: Option<bool>,
+    #[serde(default)]
+    pub fetch_page_info: Option<bool>,
+    #[serde(default)]
+    pub duplicate_links: Option<DuplicateLinkPolicy>,
+    #[serde(default)]
+    pub strip_tracking_params: Option<bool>,
+    #[serde(default)]
+    pub rewrite_rules: Option<Vec<RewriteRule>>,
+    #[serde(default)]
+    pub watch_clipboard: Option<bool>,
+    #[serde(default)]
+    pub review_uploads: Option<bool>,
+    #[serde(default)]
+    pub max_upload_size_mb: Option<u64>,
+    #[serde(default)]
+    pub allowed_upload_types: Option<Vec<String>>,
+    #[serde(default)]
+    pub strip_image_metadata: Option<bool>,
+    #[serde(default)]
+    pub clipboard_image_format: Option<ImageOutputFormat>,
+    #[serde(default)]
+    pub image_quality: Option<u8>,
+    #[serde(default)]
+    pub image_max_dimension: Option<u32>,
+    #[serde(default)]
+    pub process_dropped_images: Option<bool>,
 }
 
 impl Config {
@@ -107,4 +142,81 @@ impl Config {
     pub fn default_file_domain(&self) -> Option<&str> {
         self.default_file_domain.as_deref()
     }
+
+    pub fn strip_image_metadata(&self) -> bool {
+        self.strip_image_metadata.unwrap_or(false)
+    }
+
+    pub fn clipboard_image_format(&self) -> ImageOutputFormat {
+        self.clipboard_image_format.unwrap_or_default()
+    }
+
+    pub fn image_quality(&self) -> u8 {
+        self.image_quality.unwrap_or(85).clamp(1, 100)
+    }
+
+    pub fn image_max_dimension(&self) -> u32 {
+        self.image_max_dimension.unwrap_or(0)
+    }
+
+    pub fn process_dropped_images(&self) -> bool {
+        self.process_dropped_images.unwrap_or(false)
+    }
+
+    /// Format copied to the clipboard after a file is uploaded
+    pub fn default_copy_format(&self) -> LinkFormat {
+        self.default_copy_format.unwrap_or_default()
+    }
+
+    /// Show recent files as a grid of thumbnails instead of a list
+    pub fn file_gallery(&self) -> bool {
+        self.file_gallery.unwrap_or(false)
+    }
+
+    /// Render Markdown texts below the editor while typing
+    pub fn markdown_preview(&self) -> bool {
+        self.markdown_preview.unwrap_or(true)
+    }
+
+    /// Look up the title and icon of a page after a link to it is shortened.
+    /// Off unless turned on, since it contacts the destination site.
+    pub fn fetch_page_info(&self) -> bool {
+        self.fetch_page_info.unwrap_or(false)
+    }
+
+    /// What to do when a URL is shortened again on the same domain
+    pub fn duplicate_links(&self) -> DuplicateLinkPolicy {
+        self.duplicate_links.unwrap_or_default()
+    }
+
+    /// Remove click identifiers like `fbclid` and `utm_*` before a URL is shortened
+    pub fn strip_tracking_params(&self) -> bool {
+        self.strip_tracking_params.unwrap_or(true)
+    }
+
+    /// Find and replace rules applied to URLs before they are shortened
+    pub fn rewrite_rules(&self) -> &[RewriteRule] {
+        self.rewrite_rules.as_deref().unwrap_or_default()
+    }
+
+    /// Offer to shorten a copied URL or upload a copied image when the window is focused
+    pub fn watch_clipboard(&self) -> bool {
+        self.watch_clipboard.unwrap_or(false)
+    }
+
+    /// Ask for confirmation before a file is uploaded
+    pub fn review_uploads(&self) -> bool {
+        self.review_uploads.unwrap_or(true)
+    }
+
+    /// Largest file accepted for upload in megabytes, 0 for no limit
+    pub fn max_upload_size_mb(&self) -> u64 {
+        self.max_upload_size_mb.unwrap_or(100)
+    }
+
+    /// MIME types (`image/png`, `video/*`) or extensions (`.zip`) accepted
+    /// for upload, empty to accept everything
+    pub fn allowed_upload_types(&self) -> &[String] {
+        self.allowed_upload_types.as_deref().unwrap_or_default()
+    }
 }
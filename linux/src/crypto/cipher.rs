@@ -0,0 +1,277 @@
+use crate::error::{AppError, Result};
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+
+/// First line of an encrypted text share, so it can be recognised when fetched
+const TEXT_HEADER: &str = "-----BEGIN SEE ENCRYPTED TEXT v1-----";
+const TEXT_FOOTER: &str = "-----END SEE ENCRYPTED TEXT v1-----";
+
+/// Prefix of encrypted file blobs
+const FILE_MAGIC: &[u8] = b"SEEENC1\0";
+
+const NONCE_LEN: usize = 12;
+
+/// Largest file that is encrypted. The whole file is encrypted in memory as one
+/// message, which takes about three times its size.
+pub const MAX_ENCRYPTED_FILE_SIZE: u64 = 256 * 1024 * 1024;
+
+/// A random AES-256-GCM key that travels in the `#fragment` of a shared URL
+#[derive(Clone)]
+pub struct EncryptionKey(Key<Aes256Gcm>);
+
+impl EncryptionKey {
+    pub fn generate() -> Self {
+        Self(Aes256Gcm::generate_key(OsRng))
+    }
+
+    /// Encode the key for use as a URL fragment
+    pub fn to_fragment(&self) -> String {
+        URL_SAFE_NO_PAD.encode(self.0.as_slice())
+    }
+
+    pub fn from_fragment(fragment: &str) -> Result<Self> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(fragment.trim().trim_start_matches('#'))
+            .map_err(|_| AppError::Crypto("Invalid key in link".to_string()))?;
+        if bytes.len() != 32 {
+            return Err(AppError::Crypto("Invalid key length".to_string()));
+        }
+        Ok(Self(*Key::<Aes256Gcm>::from_slice(&bytes)))
+    }
+}
+
+pub struct ContentCipher;
+
+impl ContentCipher {
+    /// Encrypt data, returning the random nonce followed by the ciphertext
+    pub fn encrypt(key: &EncryptionKey, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let cipher = Aes256Gcm::new(&key.0);
+        let nonce = Aes256Gcm::generate_nonce(OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| AppError::Crypto("Encryption failed".to_string()))?;
+
+        let mut output = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+        output.extend_from_slice(&nonce);
+        output.extend_from_slice(&ciphertext);
+        Ok(output)
+    }
+
+    /// Decrypt data produced by [`ContentCipher::encrypt`]
+    pub fn decrypt(key: &EncryptionKey, data: &[u8]) -> Result<Vec<u8>> {
+        if data.len() < NONCE_LEN {
+            return Err(AppError::Crypto("Encrypted data is truncated".to_string()));
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        Aes256Gcm::new(&key.0)
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| AppError::Crypto("Wrong key or corrupted data".to_string()))
+    }
+
+    /// Encrypt text into an armored block that is safe to store as a text share
+    pub fn encrypt_text(key: &EncryptionKey, content: &str) -> Result<String> {
+        let data = Self::encrypt(key, content.as_bytes())?;
+        let encoded = STANDARD.encode(data);
+
+        let mut armored = String::from(TEXT_HEADER);
+        for line in encoded.as_bytes().chunks(64) {
+            armored.push('\n');
+            armored.push_str(std::str::from_utf8(line).unwrap_or_default());
+        }
+        armored.push('\n');
+        armored.push_str(TEXT_FOOTER);
+        Ok(armored)
+    }
+
+    pub fn decrypt_text(key: &EncryptionKey, armored: &str) -> Result<String> {
+        let body = armored
+            .trim()
+            .strip_prefix(TEXT_HEADER)
+            .and_then(|s| s.strip_suffix(TEXT_FOOTER))
+            .ok_or_else(|| AppError::Crypto("Not an encrypted text share".to_string()))?;
+        let encoded: String = body.split_whitespace().collect();
+        let data = STANDARD
+            .decode(encoded)
+            .map_err(|_| AppError::Crypto("Encrypted text is corrupted".to_string()))?;
+
+        let plaintext = Self::decrypt(key, &data)?;
+        String::from_utf8(plaintext)
+            .map_err(|_| AppError::Crypto("Decrypted text is not valid UTF-8".to_string()))
+    }
+
+    /// Encrypt a file together with its name, so both can be restored on download
+    pub fn encrypt_file(key: &EncryptionKey, filename: &str, contents: &[u8]) -> Result<Vec<u8>> {
+        let name = filename.as_bytes();
+        let name_len = u16::try_from(name.len())
+            .map_err(|_| AppError::Crypto("File name is too long".to_string()))?;
+
+        let mut plaintext = Vec::with_capacity(2 + name.len() + contents.len());
+        plaintext.extend_from_slice(&name_len.to_be_bytes());
+        plaintext.extend_from_slice(name);
+        plaintext.extend_from_slice(contents);
+
+        let data = Self::encrypt(key, &plaintext)?;
+        let mut output = Vec::with_capacity(FILE_MAGIC.len() + data.len());
+        output.extend_from_slice(FILE_MAGIC);
+        output.extend_from_slice(&data);
+        Ok(output)
+    }
+
+    /// Decrypt a blob from [`ContentCipher::encrypt_file`] into its filename and contents
+    pub fn decrypt_file(key: &EncryptionKey, data: &[u8]) -> Result<(String, Vec<u8>)> {
+        let data = data
+            .strip_prefix(FILE_MAGIC)
+            .ok_or_else(|| AppError::Crypto("Not an encrypted file".to_string()))?;
+        let mut plaintext = Self::decrypt(key, data)?;
+
+        if plaintext.len() < 2 {
+            return Err(AppError::Crypto("Encrypted file is truncated".to_string()));
+        }
+        let name_len = u16::from_be_bytes([plaintext[0], plaintext[1]]) as usize;
+        if plaintext.len() < 2 + name_len {
+            return Err(AppError::Crypto("Encrypted file is truncated".to_string()));
+        }
+
+        let filename = String::from_utf8_lossy(&plaintext[2..2 + name_len]).into_owned();
+        let contents = plaintext.split_off(2 + name_len);
+        Ok((filename, contents))
+    }
+
+    pub fn is_encrypted_text(content: &str) -> bool {
+        content.trim_start().starts_with(TEXT_HEADER)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_round_trips_through_armor() {
+        let key = EncryptionKey::generate();
+        let content = "Meet at noon.\nBring the 🔑.";
+        let armored = ContentCipher::encrypt_text(&key, content).unwrap();
+
+        assert!(armored.starts_with(TEXT_HEADER));
+        assert!(armored.ends_with(TEXT_FOOTER));
+        assert!(!armored.contains("noon"));
+        assert!(
+            armored
+                .lines()
+                .all(|line| line.len() <= 64 || line.starts_with("-----"))
+        );
+        assert_eq!(
+            ContentCipher::decrypt_text(&key, &armored).unwrap(),
+            content
+        );
+    }
+
+    #[test]
+    fn wrong_key_fails() {
+        let armored = ContentCipher::encrypt_text(&EncryptionKey::generate(), "secret").unwrap();
+        let result = ContentCipher::decrypt_text(&EncryptionKey::generate(), &armored);
+        assert!(matches!(result, Err(AppError::Crypto(_))));
+    }
+
+    #[test]
+    fn flipped_ciphertext_byte_fails_authentication() {
+        let key = EncryptionKey::generate();
+        let mut data = ContentCipher::encrypt(&key, b"secret").unwrap();
+        let last = data.len() - 1;
+        data[NONCE_LEN] ^= 0x01;
+        assert!(ContentCipher::decrypt(&key, &data).is_err());
+
+        data[NONCE_LEN] ^= 0x01;
+        data[last] ^= 0x80;
+        assert!(ContentCipher::decrypt(&key, &data).is_err());
+    }
+
+    #[test]
+    fn key_round_trips_through_fragment() {
+        let key = EncryptionKey::generate();
+        let fragment = key.to_fragment();
+        let restored = EncryptionKey::from_fragment(&format!("#{}", fragment)).unwrap();
+
+        let data = ContentCipher::encrypt(&key, b"hello").unwrap();
+        assert_eq!(ContentCipher::decrypt(&restored, &data).unwrap(), b"hello");
+    }
+
+    #[test]
+    fn rejects_bad_fragments() {
+        let short = URL_SAFE_NO_PAD.encode([7u8; 16]);
+        let long = URL_SAFE_NO_PAD.encode([7u8; 33]);
+        for fragment in [short.as_str(), long.as_str(), "not base64!", ""] {
+            assert!(
+                EncryptionKey::from_fragment(fragment).is_err(),
+                "accepted {:?}",
+                fragment
+            );
+        }
+    }
+
+    #[test]
+    fn recognises_encrypted_text() {
+        let armored = ContentCipher::encrypt_text(&EncryptionKey::generate(), "x").unwrap();
+        assert!(ContentCipher::is_encrypted_text(&armored));
+        assert!(ContentCipher::is_encrypted_text(&format!(
+            " \n\t{}",
+            armored
+        )));
+        assert!(!ContentCipher::is_encrypted_text("Just some notes"));
+        assert!(!ContentCipher::is_encrypted_text(&format!(
+            "Note: {}",
+            armored
+        )));
+    }
+
+    #[test]
+    fn file_round_trips_with_its_name() {
+        let key = EncryptionKey::generate();
+        let contents: Vec<u8> = (0..=255).cycle().take(5000).collect();
+        let blob = ContentCipher::encrypt_file(&key, "Quarterly report.pdf", &contents).unwrap();
+
+        assert!(blob.starts_with(FILE_MAGIC));
+        let (filename, decrypted) = ContentCipher::decrypt_file(&key, &blob).unwrap();
+        assert_eq!(filename, "Quarterly report.pdf");
+        assert_eq!(decrypted, contents);
+    }
+
+    #[test]
+    fn rejects_truncated_files() {
+        let key = EncryptionKey::generate();
+        let blob = ContentCipher::encrypt_file(&key, "a.txt", b"contents").unwrap();
+        for len in [
+            FILE_MAGIC.len(),
+            FILE_MAGIC.len() + NONCE_LEN,
+            blob.len() - 1,
+        ] {
+            assert!(ContentCipher::decrypt_file(&key, &blob[..len]).is_err());
+        }
+
+        // Authentic, but the name runs past the end of the plaintext
+        let mut blob = FILE_MAGIC.to_vec();
+        blob.extend(ContentCipher::encrypt(&key, &[0, 9, b'a']).unwrap());
+        assert!(ContentCipher::decrypt_file(&key, &blob).is_err());
+    }
+
+    #[test]
+    fn rejects_files_without_magic() {
+        let key = EncryptionKey::generate();
+        let blob = ContentCipher::encrypt_file(&key, "a.txt", b"contents").unwrap();
+        let result = ContentCipher::decrypt_file(&key, &blob[FILE_MAGIC.len()..]);
+        assert!(matches!(result, Err(AppError::Crypto(_))));
+    }
+
+    #[test]
+    fn rejects_names_too_long_to_frame() {
+        let key = EncryptionKey::generate();
+        let name = "a".repeat(u16::MAX as usize + 1);
+        assert!(ContentCipher::encrypt_file(&key, &name, b"contents").is_err());
+
+        let name = "a".repeat(u16::MAX as usize);
+        let blob = ContentCipher::encrypt_file(&key, &name, b"contents").unwrap();
+        assert_eq!(ContentCipher::decrypt_file(&key, &blob).unwrap().0, name);
+    }
+}
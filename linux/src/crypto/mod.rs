@@ -0,0 +1,3 @@
+mod cipher;
+
+pub use cipher::{ContentCipher, EncryptionKey, MAX_ENCRYPTED_FILE_SIZE};
@@ -26,6 +26,15 @@ pub enum AppError {
     #[error("QR code generation error: {0}")]
     QrCode(String),
 
+    #[error("Encryption error: {0}")]
+    Crypto(String),
+
+    #[error("Upload not allowed: {0}")]
+    UploadRejected(String),
+
+    #[error("Cannot open file: {0}")]
+    UnsupportedFile(String),
+
     #[error("Image error: {0}")]
     Image(#[from] image::ImageError),
 
@@ -34,6 +43,9 @@ pub enum AppError {
 
     #[error("Invalid URL: {0}")]
     InvalidUrl(String),
+
+    #[error("{0}")]
+    Usage(String),
 }
 
 pub type Result<T> = std::result::Result<T, AppError>;
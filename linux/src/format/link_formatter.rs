@@ -0,0 +1,185 @@
+use crate::storage::FileEntry;
+use serde::{Deserialize, Serialize};
+
+/// How an uploaded file's link is copied
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkFormat {
+    #[default]
+    Direct,
+    SharePage,
+    Bbcode,
+    BbcodeWithLink,
+    BbcodeDirectLink,
+    Html,
+    HtmlWithLink,
+    HtmlDirectLink,
+    Markdown,
+}
+
+impl LinkFormat {
+    pub const ALL: [LinkFormat; 9] = [
+        LinkFormat::Direct,
+        LinkFormat::SharePage,
+        LinkFormat::Bbcode,
+        LinkFormat::BbcodeWithLink,
+        LinkFormat::BbcodeDirectLink,
+        LinkFormat::Html,
+        LinkFormat::HtmlWithLink,
+        LinkFormat::HtmlDirectLink,
+        LinkFormat::Markdown,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkFormat::Direct => "direct",
+            LinkFormat::SharePage => "share_page",
+            LinkFormat::Bbcode => "bbcode",
+            LinkFormat::BbcodeWithLink => "bbcode_with_link",
+            LinkFormat::BbcodeDirectLink => "bbcode_direct_link",
+            LinkFormat::Html => "html",
+            LinkFormat::HtmlWithLink => "html_with_link",
+            LinkFormat::HtmlDirectLink => "html_direct_link",
+            LinkFormat::Markdown => "markdown",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.as_str() == value)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LinkFormat::Direct => "Direct Link",
+            LinkFormat::SharePage => "Share Page",
+            LinkFormat::Bbcode => "BBCode",
+            LinkFormat::BbcodeWithLink => "BBCode w/ Link",
+            LinkFormat::BbcodeDirectLink => "BBCode w/ Direct Link",
+            LinkFormat::Html => "HTML",
+            LinkFormat::HtmlWithLink => "HTML w/ Link",
+            LinkFormat::HtmlDirectLink => "HTML w/ Direct Link",
+            LinkFormat::Markdown => "Markdown",
+        }
+    }
+}
+
+/// Kind of media, deciding which tag embeds a file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileCategory {
+    Image,
+    Audio,
+    Video,
+    Other,
+}
+
+pub struct LinkFormatter;
+
+impl LinkFormatter {
+    /// Format the links of an uploaded file
+    pub fn format(entry: &FileEntry, format: LinkFormat) -> String {
+        let direct = entry.url.as_str();
+        let page = entry.page_url.as_deref().unwrap_or(direct);
+        let name = entry.filename.as_str();
+        let category = Self::category(entry);
+
+        match format {
+            LinkFormat::Direct => direct.to_string(),
+            LinkFormat::SharePage => page.to_string(),
+            LinkFormat::Bbcode => Self::bbcode(category, name, direct, None),
+            LinkFormat::BbcodeWithLink => Self::bbcode(category, name, direct, Some(page)),
+            LinkFormat::BbcodeDirectLink => Self::bbcode(category, name, direct, Some(direct)),
+            LinkFormat::Html => Self::html(category, name, direct, None),
+            LinkFormat::HtmlWithLink => Self::html(category, name, direct, Some(page)),
+            LinkFormat::HtmlDirectLink => Self::html(category, name, direct, Some(direct)),
+            LinkFormat::Markdown => Self::markdown(category, name, direct),
+        }
+    }
+
+    /// Category from the sniffed MIME type, or the file extension for older entries
+    pub fn category(entry: &FileEntry) -> FileCategory {
+        if let Some(ref mime_type) = entry.mime_type {
+            match mime_type.split('/').next() {
+                Some("image") => return FileCategory::Image,
+                Some("audio") => return FileCategory::Audio,
+                Some("video") => return FileCategory::Video,
+                _ => {}
+            }
+        }
+
+        let extension = entry
+            .filename
+            .rsplit_once('.')
+            .map(|(_, ext)| ext.to_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "jpg" | "jpeg" | "png" | "gif" | "webp" | "bmp" | "svg" | "heic" | "avif" | "ico"
+            | "tiff" => FileCategory::Image,
+            "mp3" | "wav" | "flac" | "aac" | "ogg" | "m4a" | "wma" => FileCategory::Audio,
+            "mp4" | "mkv" | "avi" | "mov" | "wmv" | "flv" | "webm" | "m4v" => FileCategory::Video,
+            _ => FileCategory::Other,
+        }
+    }
+
+    /// BBCode tag, wrapped in a link to `link` for images and other files.
+    /// Audio and video are never wrapped.
+    fn bbcode(category: FileCategory, name: &str, direct: &str, link: Option<&str>) -> String {
+        match (category, link) {
+            (FileCategory::Image, None) => format!("[img]{}[/img]", direct),
+            (FileCategory::Image, Some(link)) => {
+                format!("[url={}][img]{}[/img][/url]", link, direct)
+            }
+            (FileCategory::Audio, _) => format!("[audio]{}[/audio]", direct),
+            (FileCategory::Video, _) => format!("[video]{}[/video]", direct),
+            (FileCategory::Other, link) => {
+                format!("[url={}]{}[/url]", link.unwrap_or(direct), name)
+            }
+        }
+    }
+
+    /// HTML tag, wrapped in an anchor to `link` for images and other files.
+    /// Audio and video are never wrapped.
+    fn html(category: FileCategory, name: &str, direct: &str, link: Option<&str>) -> String {
+        let name = escape_html(name);
+        let direct = escape_html(direct);
+
+        match (category, link) {
+            (FileCategory::Image, None) => {
+                format!(r#"<img src="{}" alt="{}" title="{}">"#, direct, name, name)
+            }
+            (FileCategory::Image, Some(link)) => format!(
+                r#"<a href="{}" target="_blank"><img src="{}" alt="{}" title="{}"></a>"#,
+                escape_html(link),
+                direct,
+                name,
+                name
+            ),
+            (FileCategory::Audio, _) => {
+                format!(r#"<audio src="{}" controls>{}</audio>"#, direct, name)
+            }
+            (FileCategory::Video, _) => {
+                format!(r#"<video src="{}" controls>{}</video>"#, direct, name)
+            }
+            (FileCategory::Other, None) => format!(r#"<a href="{}">{}</a>"#, direct, name),
+            (FileCategory::Other, Some(link)) => format!(
+                r#"<a href="{}" target="_blank">{}</a>"#,
+                escape_html(link),
+                name
+            ),
+        }
+    }
+
+    fn markdown(category: FileCategory, name: &str, direct: &str) -> String {
+        let name = name.replace('[', "\\[").replace(']', "\\]");
+        match category {
+            FileCategory::Image => format!("![{}]({})", name, direct),
+            _ => format!("[{}]({})", name, direct),
+        }
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
@@ -0,0 +1,3 @@
+mod link_formatter;
+
+pub use link_formatter::{FileCategory, LinkFormat, LinkFormatter};
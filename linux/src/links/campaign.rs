@@ -0,0 +1,92 @@
+use super::rewrite::query_params;
+use serde::{Deserialize, Serialize};
+use url::{form_urlencoded, Url};
+
+/// UTM parameters added to a link's destination so analytics can tell
+/// where a visit came from
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Campaign {
+    #[serde(default)]
+    pub source: String,
+    #[serde(default)]
+    pub medium: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub term: String,
+    #[serde(default)]
+    pub content: String,
+}
+
+impl Campaign {
+    pub fn is_empty(&self) -> bool {
+        self.params().iter().all(|(_, value)| value.is_empty())
+    }
+
+    /// Destination tagged with the campaign, replacing any UTM values it already has.
+    /// Other query parameters are kept in order and as they were written.
+    pub fn apply(&self, url: &Url) -> Url {
+        let params = self.params();
+        if params.iter().all(|(_, value)| value.is_empty()) {
+            return url.clone();
+        }
+
+        let replaced = |key: &str| params.iter().any(|(k, v)| *k == key && !v.is_empty());
+        let kept = query_params(url.query().unwrap_or_default(), |key| !replaced(key));
+        let added = form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(params.iter().filter(|(_, value)| !value.is_empty()))
+            .finish();
+
+        let mut tagged = url.clone();
+        let mut query = kept.join("&");
+        if !query.is_empty() {
+            query.push('&');
+        }
+        query.push_str(&added);
+        tagged.set_query(Some(&query));
+        tagged
+    }
+
+    /// Trimmed parameter values by their query name
+    fn params(&self) -> [(&'static str, &str); 5] {
+        [
+            ("utm_source", self.source.trim()),
+            ("utm_medium", self.medium.trim()),
+            ("utm_campaign", self.name.trim()),
+            ("utm_term", self.term.trim()),
+            ("utm_content", self.content.trim()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_utm_values_and_keeps_other_params_as_written() {
+        let campaign = Campaign {
+            source: "news letter".to_string(),
+            name: "spring".to_string(),
+            ..Default::default()
+        };
+        let url = Url::parse("https://example.com/?q=a%20b&utm_source=old&flag&sig=x+y").unwrap();
+        assert_eq!(
+            campaign.apply(&url).as_str(),
+            "https://example.com/?q=a%20b&flag&sig=x+y&utm_source=news+letter&utm_campaign=spring"
+        );
+    }
+
+    #[test]
+    fn tags_urls_without_a_query() {
+        let campaign = Campaign {
+            medium: "email".to_string(),
+            ..Default::default()
+        };
+        let url = Url::parse("https://example.com/page#top").unwrap();
+        assert_eq!(
+            campaign.apply(&url).as_str(),
+            "https://example.com/page?utm_medium=email#top"
+        );
+    }
+}
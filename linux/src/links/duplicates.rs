@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+/// What to do with a URL that was already shortened on the same domain
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateLinkPolicy {
+    #[default]
+    Ask,
+    Reuse,
+    CreateNew,
+}
+
+impl DuplicateLinkPolicy {
+    pub const ALL: [DuplicateLinkPolicy; 3] = [
+        DuplicateLinkPolicy::Ask,
+        DuplicateLinkPolicy::Reuse,
+        DuplicateLinkPolicy::CreateNew,
+    ];
+
+    /// Policy for when nobody is there to ask, as on the command line
+    pub fn unattended(self) -> Self {
+        match self {
+            DuplicateLinkPolicy::Ask => DuplicateLinkPolicy::Reuse,
+            policy => policy,
+        }
+    }
+}
@@ -0,0 +1,381 @@
+use crate::error::{AppError, Result};
+use reqwest::blocking::Client as HttpClient;
+use reqwest::header::LOCATION;
+use reqwest::redirect::Policy;
+use std::net::IpAddr;
+use std::time::Duration;
+use url::{Host, Url};
+
+/// Longest wait for each hop
+const HOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Redirects followed before giving up
+const MAX_HOPS: usize = 10;
+
+/// Chains longer than this are flagged
+const LONG_CHAIN: usize = 4;
+
+/// Something about a hop worth a second look before clicking
+#[derive(Debug, Clone, PartialEq)]
+pub enum HopWarning {
+    /// Redirected from HTTPS to plain HTTP
+    Downgrade,
+    /// Plain HTTP, readable and changeable on the way
+    Insecure,
+    /// A raw IP address instead of a domain name
+    IpAddress,
+    /// A private or local network address
+    LocalAddress,
+    /// An international domain name, which can imitate another one
+    Punycode(String),
+    /// A user name before the host, as in `https://bank.com@evil.com`
+    Credentials,
+    /// A port other than the default for the scheme
+    UnusualPort(u16),
+    /// Sent somewhere that is not a web page, like `javascript:` or `file:`
+    UnsupportedScheme(String),
+    /// Back to an address already visited
+    Loop,
+}
+
+impl HopWarning {
+    pub fn description(&self) -> String {
+        match self {
+            HopWarning::Downgrade => "Leaves HTTPS for an unencrypted connection".to_string(),
+            HopWarning::Insecure => "Unencrypted connection".to_string(),
+            HopWarning::IpAddress => "Uses an IP address instead of a domain name".to_string(),
+            HopWarning::LocalAddress => "Points into a local or private network".to_string(),
+            HopWarning::Punycode(name) => {
+                format!("International domain name, shown as \"{}\"", name)
+            }
+            HopWarning::Credentials => {
+                "Has a user name before the host, which can disguise the real site".to_string()
+            }
+            HopWarning::UnusualPort(port) => format!("Uses unusual port {}", port),
+            HopWarning::UnsupportedScheme(scheme) => {
+                format!("Redirects to a \"{}\" link, which was not followed", scheme)
+            }
+            HopWarning::Loop => "Redirects back to an address already visited".to_string(),
+        }
+    }
+}
+
+/// One request in a redirect chain
+#[derive(Debug, Clone)]
+pub struct Hop {
+    pub url: Url,
+    /// HTTP status, `None` when the request failed
+    pub status: Option<u16>,
+    /// Where the server redirected to, as sent
+    pub location: Option<String>,
+    pub warnings: Vec<HopWarning>,
+}
+
+/// Every hop from a link to where it ends up
+#[derive(Debug, Clone, Default)]
+pub struct RedirectChain {
+    pub hops: Vec<Hop>,
+    /// Why the chain stopped before reaching a page, if it did
+    pub error: Option<String>,
+}
+
+impl RedirectChain {
+    /// Number of redirects followed
+    pub fn redirects(&self) -> usize {
+        self.hops.len().saturating_sub(1)
+    }
+
+    /// Final page, when the chain ended with a successful response
+    pub fn destination(&self) -> Option<&Url> {
+        let last = self.hops.last()?;
+        let ok = self.error.is_none() && last.status.is_some_and(|s| (200..300).contains(&s));
+        ok.then_some(&last.url)
+    }
+
+    pub fn is_suspicious(&self) -> bool {
+        self.redirects() > LONG_CHAIN || self.hops.iter().any(|h| !h.warnings.is_empty())
+    }
+}
+
+/// Follows redirects one at a time without downloading any page
+pub struct RedirectTracer {
+    client: HttpClient,
+    max_hops: usize,
+}
+
+impl RedirectTracer {
+    pub fn new() -> Result<Self> {
+        Self::with_limits(HOP_TIMEOUT, MAX_HOPS)
+    }
+
+    pub fn with_limits(timeout: Duration, max_hops: usize) -> Result<Self> {
+        let client = HttpClient::builder()
+            .timeout(timeout)
+            .redirect(Policy::none())
+            .user_agent(concat!("see/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e: reqwest::Error| AppError::Api(e.to_string()))?;
+        Ok(Self { client, max_hops })
+    }
+
+    /// Follow a link to its destination. Failures part way are recorded in the chain.
+    pub fn trace(&self, url: &Url) -> RedirectChain {
+        let mut chain = RedirectChain::default();
+        let mut next = Some(url.clone());
+
+        while let Some(url) = next.take() {
+            let mut warnings = Self::inspect(&url);
+            let from_https = chain.hops.last().is_some_and(|h| h.url.scheme() == "https");
+            if from_https && url.scheme() == "http" {
+                warnings.retain(|w| *w != HopWarning::Insecure);
+                warnings.insert(0, HopWarning::Downgrade);
+            }
+            let looped = chain.hops.iter().any(|h| h.url == url);
+            if looped {
+                warnings.push(HopWarning::Loop);
+            }
+
+            let mut hop = Hop {
+                url,
+                status: None,
+                location: None,
+                warnings,
+            };
+            if looped {
+                chain.hops.push(hop);
+                chain.error = Some("Redirect loop".to_string());
+                break;
+            }
+            // Every hop after the first was reached through a redirect
+            if chain.hops.len() > self.max_hops {
+                chain.hops.push(hop);
+                chain.error = Some(format!("More than {} redirects", self.max_hops));
+                break;
+            }
+
+            // The body is never read; dropping the response closes the connection
+            let response = match self.client.get(hop.url.as_str()).send() {
+                Ok(response) => response,
+                Err(e) => {
+                    chain.error = Some(e.to_string());
+                    chain.hops.push(hop);
+                    break;
+                }
+            };
+            let status = response.status();
+            hop.status = Some(status.as_u16());
+            hop.location = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|v| v.to_str().ok())
+                .map(String::from);
+
+            if status.is_redirection() {
+                match hop.location.as_deref().map(|l| hop.url.join(l)) {
+                    Some(Ok(location)) if matches!(location.scheme(), "http" | "https") => {
+                        next = Some(location);
+                    }
+                    Some(Ok(location)) => {
+                        let scheme = location.scheme().to_string();
+                        hop.warnings.push(HopWarning::UnsupportedScheme(scheme));
+                        chain.error = Some("Redirects away from the web".to_string());
+                    }
+                    Some(Err(e)) => chain.error = Some(format!("Invalid redirect: {}", e)),
+                    None => chain.error = Some("Redirect without a location".to_string()),
+                }
+            } else if !status.is_success() {
+                chain.error = Some(format!("HTTP error {}", status));
+            }
+            chain.hops.push(hop);
+        }
+
+        chain
+    }
+
+    /// Warnings that follow from an address alone
+    fn inspect(url: &Url) -> Vec<HopWarning> {
+        let mut warnings = Vec::new();
+
+        if url.scheme() == "http" {
+            warnings.push(HopWarning::Insecure);
+        }
+        if !url.username().is_empty() || url.password().is_some() {
+            warnings.push(HopWarning::Credentials);
+        }
+
+        match url.host() {
+            Some(Host::Ipv4(ip)) => {
+                warnings.push(HopWarning::IpAddress);
+                if Self::is_local(&IpAddr::V4(ip)) {
+                    warnings.push(HopWarning::LocalAddress);
+                }
+            }
+            Some(Host::Ipv6(ip)) => {
+                warnings.push(HopWarning::IpAddress);
+                if Self::is_local(&IpAddr::V6(ip)) {
+                    warnings.push(HopWarning::LocalAddress);
+                }
+            }
+            Some(Host::Domain(domain)) => {
+                if domain == "localhost" || domain.ends_with(".localhost") {
+                    warnings.push(HopWarning::LocalAddress);
+                }
+                if domain.split('.').any(|label| label.starts_with("xn--")) {
+                    let unicode = url::quirks::domain_to_unicode(domain);
+                    warnings.push(HopWarning::Punycode(unicode));
+                }
+            }
+            None => {}
+        }
+
+        if let Some(port) = url.port() {
+            warnings.push(HopWarning::UnusualPort(port));
+        }
+        warnings
+    }
+
+    fn is_local(ip: &IpAddr) -> bool {
+        match ip {
+            IpAddr::V4(ip) => {
+                ip.is_loopback() || ip.is_private() || ip.is_link_local() || ip.is_unspecified()
+            }
+            IpAddr::V6(ip) => {
+                ip.is_loopback()
+                    || ip.is_unspecified()
+                    // Unique local (fc00::/7) and link-local (fe80::/10)
+                    || (ip.segments()[0] & 0xfe00) == 0xfc00
+                    || (ip.segments()[0] & 0xffc0) == 0xfe80
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{StubRequest, StubResponse, StubServer};
+    use std::sync::{Arc, Mutex};
+
+    fn tracer(max_hops: usize) -> RedirectTracer {
+        RedirectTracer::with_limits(Duration::from_secs(5), max_hops).unwrap()
+    }
+
+    fn trace(server: &StubServer, path: &str) -> RedirectChain {
+        tracer(MAX_HOPS).trace(&Url::parse(&server.url(path)).unwrap())
+    }
+
+    #[test]
+    fn records_each_hop_of_a_chain() {
+        let end = Arc::new(Mutex::new(String::new()));
+        let server = {
+            let end = end.clone();
+            StubServer::start(move |request: &StubRequest| match request.path.as_str() {
+                "/start" => Some(StubResponse::redirect(301, &end.lock().unwrap())),
+                "/middle" => Some(StubResponse::redirect(302, "end")),
+                "/end" => Some(StubResponse::html("<title>End</title>")),
+                _ => Some(StubResponse::new(404)),
+            })
+        };
+        *end.lock().unwrap() = server.url("/middle");
+
+        let chain = trace(&server, "/start");
+        let statuses: Vec<_> = chain.hops.iter().map(|h| h.status).collect();
+        assert_eq!(statuses, [Some(301), Some(302), Some(200)]);
+        let locations: Vec<_> = chain.hops.iter().map(|h| h.location.as_deref()).collect();
+        assert_eq!(
+            locations,
+            [Some(server.url("/middle").as_str()), Some("end"), None]
+        );
+        assert_eq!(chain.redirects(), 2);
+        assert!(chain.error.is_none());
+        assert_eq!(chain.destination().unwrap().as_str(), server.url("/end"));
+    }
+
+    #[test]
+    fn resolves_relative_locations() {
+        let server = StubServer::start(|request: &StubRequest| match request.path.as_str() {
+            "/dir/page" => Some(StubResponse::redirect(307, "../other?x=1")),
+            "/other?x=1" => Some(StubResponse::new(204)),
+            _ => Some(StubResponse::new(404)),
+        });
+
+        let chain = trace(&server, "/dir/page");
+        assert_eq!(chain.hops.len(), 2);
+        assert_eq!(chain.hops[1].url.as_str(), server.url("/other?x=1"));
+        assert_eq!(chain.hops[1].status, Some(204));
+        assert!(chain.error.is_none());
+    }
+
+    #[test]
+    fn stops_at_a_loop() {
+        let server = StubServer::start(|request: &StubRequest| match request.path.as_str() {
+            "/a" => Some(StubResponse::redirect(302, "/b")),
+            "/b" => Some(StubResponse::redirect(302, "/a")),
+            _ => Some(StubResponse::new(404)),
+        });
+
+        let chain = trace(&server, "/a");
+        assert_eq!(chain.hops.len(), 3);
+        let last = chain.hops.last().unwrap();
+        assert_eq!(last.url.path(), "/a");
+        assert_eq!(last.status, None);
+        assert!(last.warnings.contains(&HopWarning::Loop));
+        assert_eq!(chain.error.as_deref(), Some("Redirect loop"));
+        assert!(chain.destination().is_none());
+    }
+
+    /// Redirects `/n/0` to `/n/1` and so on, with a page at `/n/{last}`
+    fn counting_server(last: usize, requests: Arc<Mutex<usize>>) -> StubServer {
+        StubServer::start(move |request: &StubRequest| {
+            *requests.lock().unwrap() += 1;
+            let n: usize = request.path.strip_prefix("/n/")?.parse().ok()?;
+            if n == last {
+                Some(StubResponse::html("<title>Last</title>"))
+            } else {
+                Some(StubResponse::redirect(302, &format!("/n/{}", n + 1)))
+            }
+        })
+    }
+
+    #[test]
+    fn follows_up_to_max_hops() {
+        let requests = Arc::new(Mutex::new(0));
+        let server = counting_server(3, requests.clone());
+
+        let chain = tracer(3).trace(&Url::parse(&server.url("/n/0")).unwrap());
+        assert!(chain.error.is_none(), "{:?}", chain.error);
+        assert_eq!(chain.redirects(), 3);
+        assert_eq!(*requests.lock().unwrap(), 4);
+    }
+
+    #[test]
+    fn gives_up_after_max_hops() {
+        let requests = Arc::new(Mutex::new(0));
+        let server = counting_server(usize::MAX, requests.clone());
+
+        let chain = tracer(3).trace(&Url::parse(&server.url("/n/0")).unwrap());
+        assert_eq!(chain.error.as_deref(), Some("More than 3 redirects"));
+        assert_eq!(*requests.lock().unwrap(), 4);
+        let last = chain.hops.last().unwrap();
+        assert_eq!(last.url.path(), "/n/4");
+        assert_eq!(last.status, None);
+    }
+
+    #[test]
+    fn does_not_follow_javascript_links() {
+        let server = StubServer::start(|_: &StubRequest| {
+            Some(StubResponse::redirect(302, "javascript:alert(1)"))
+        });
+
+        let chain = trace(&server, "/a");
+        assert_eq!(chain.hops.len(), 1);
+        let hop = &chain.hops[0];
+        assert_eq!(hop.location.as_deref(), Some("javascript:alert(1)"));
+        assert!(
+            hop.warnings
+                .contains(&HopWarning::UnsupportedScheme("javascript".to_string()))
+        );
+        assert_eq!(chain.error.as_deref(), Some("Redirects away from the web"));
+        assert!(chain.destination().is_none());
+    }
+}
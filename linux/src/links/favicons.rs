@@ -0,0 +1,81 @@
+use crate::config::Config;
+use crate::error::{AppError, Result};
+use crate::media::FileInspector;
+use std::fs;
+use std::path::PathBuf;
+use url::Url;
+
+/// Site icons of shortened links, one per host
+#[derive(Debug)]
+pub struct FaviconCache {
+    dir: PathBuf,
+}
+
+impl FaviconCache {
+    pub fn load() -> Result<Self> {
+        let dir = Config::data_dir()?.join("favicons");
+        Ok(Self { dir })
+    }
+
+    /// Path of the cached icon for the site a link points to, if there is one
+    pub fn get(&self, page_url: &str) -> Option<PathBuf> {
+        let path = self.icon_path(page_url)?;
+        path.exists().then_some(path)
+    }
+
+    /// Keep an icon for the site a link points to.
+    /// Anything that is not an image, like an error page, is refused.
+    pub fn store(&self, page_url: &str, bytes: &[u8]) -> Result<PathBuf> {
+        let path = self
+            .icon_path(page_url)
+            .ok_or_else(|| AppError::InvalidUrl(page_url.to_string()))?;
+        if !FileInspector::sniff_mime(bytes).is_some_and(|m| m.starts_with("image/")) {
+            return Err(AppError::UnsupportedFile(
+                "favicon is not an image".to_string(),
+            ));
+        }
+
+        // Write under a temporary name so a half-written file is never picked up
+        fs::create_dir_all(&self.dir)?;
+        let partial = path.with_extension("part");
+        fs::write(&partial, bytes)?;
+        fs::rename(&partial, &path)?;
+        Ok(path)
+    }
+
+    /// Total size of the cached icons in bytes
+    pub fn size(&self) -> u64 {
+        fs::read_dir(&self.dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter_map(|e| e.metadata().ok())
+                    .map(|m| m.len())
+                    .sum()
+            })
+            .unwrap_or(0)
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        if self.dir.exists() {
+            fs::remove_dir_all(&self.dir)?;
+        }
+        Ok(())
+    }
+
+    fn icon_path(&self, page_url: &str) -> Option<PathBuf> {
+        let url = Url::parse(page_url).ok()?;
+        let host: String = url
+            .host_str()?
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '.' | '-') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        Some(self.dir.join(format!("{}.icon", host)))
+    }
+}
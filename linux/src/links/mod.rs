@@ -0,0 +1,15 @@
+mod campaign;
+mod duplicates;
+mod expand;
+mod favicons;
+mod normalize;
+mod preview;
+mod rewrite;
+
+pub use campaign::Campaign;
+pub use duplicates::DuplicateLinkPolicy;
+pub use expand::{RedirectChain, RedirectTracer};
+pub use favicons::FaviconCache;
+pub use normalize::UrlNormalizer;
+pub use preview::PageFetcher;
+pub use rewrite::{RewriteRule, UrlRewriter};
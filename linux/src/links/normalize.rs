@@ -0,0 +1,199 @@
+use crate::error::{AppError, Result};
+use url::{Host, Url};
+
+/// Schemes the shortener accepts
+const SUPPORTED_SCHEMES: &[&str] = &["http", "https"];
+
+/// Characters that wrap a pasted URL rather than belong to it
+const OPENING: &[char] = &['<', '"', '\'', '(', '[', '“', '‘'];
+
+/// Characters that end a sentence or close a wrapper around a pasted URL
+const TRAILING_PUNCTUATION: &[char] =
+    &['.', ',', ';', ':', '!', '?', '>', '\'', '"', '’', '”', '…'];
+
+pub struct UrlNormalizer;
+
+impl UrlNormalizer {
+    /// Clean up a typed or pasted URL and check it can be shortened.
+    ///
+    /// Surrounding whitespace, quotes, angle brackets and sentence punctuation
+    /// are dropped, `https://` is added when there is no scheme, and
+    /// international domain names are converted to punycode.
+    pub fn normalize(input: &str) -> Result<Url> {
+        let text = Self::trim(input);
+        if text.is_empty() {
+            return Err(AppError::InvalidUrl("the URL is empty".to_string()));
+        }
+
+        let candidate = match Self::scheme(text) {
+            Some(scheme) => {
+                if !SUPPORTED_SCHEMES.contains(&scheme.to_lowercase().as_str()) {
+                    return Err(AppError::InvalidUrl(format!(
+                        "\"{}\" links are not supported",
+                        scheme
+                    )));
+                }
+                text.to_string()
+            }
+            None => format!("https://{}", text.trim_start_matches('/')),
+        };
+
+        let url = Url::parse(&candidate).map_err(|e| AppError::InvalidUrl(e.to_string()))?;
+        match url.host() {
+            Some(Host::Domain(domain)) => {
+                let domain = domain.trim_end_matches('.');
+                if !domain.contains('.') || domain.ends_with('-') {
+                    return Err(AppError::InvalidUrl(format!(
+                        "\"{}\" is not a full domain name",
+                        domain
+                    )));
+                }
+            }
+            Some(Host::Ipv4(_)) | Some(Host::Ipv6(_)) => {}
+            None => return Err(AppError::InvalidUrl("the URL has no host".to_string())),
+        }
+
+        Ok(url)
+    }
+
+    /// Strip whitespace, wrapping characters and trailing punctuation.
+    /// From a sentence with a link in it, only the link is kept.
+    fn trim(input: &str) -> &str {
+        let mut text = input.trim();
+        if text.contains(char::is_whitespace) {
+            if let Some(word) = text.split_whitespace().find(|w| w.contains("://")) {
+                text = word;
+            }
+        }
+
+        text = text.trim_start_matches(OPENING);
+        loop {
+            let before = text;
+            text = text.trim_end_matches(TRAILING_PUNCTUATION);
+
+            // A closing bracket belongs to the URL only if it also opens one, as on Wikipedia
+            for (open, close) in [('(', ')'), ('[', ']')] {
+                if text.ends_with(close) && text.matches(close).count() > text.matches(open).count()
+                {
+                    text = &text[..text.len() - 1];
+                }
+            }
+
+            if text == before {
+                return text;
+            }
+        }
+    }
+
+    /// Scheme at the start of the text, if it has one.
+    /// The digits in "example.com:8080" are a port, not a scheme.
+    fn scheme(text: &str) -> Option<&str> {
+        let (scheme, rest) = text.split_once(':')?;
+        let valid = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+        let is_port = rest.starts_with(|c: char| c.is_ascii_digit());
+        (valid && (rest.starts_with("//") || !is_port)).then_some(scheme)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalized(input: &str) -> String {
+        match UrlNormalizer::normalize(input) {
+            Ok(url) => url.to_string(),
+            Err(e) => panic!("{:?} was rejected: {}", input, e),
+        }
+    }
+
+    fn rejection(input: &str) -> String {
+        match UrlNormalizer::normalize(input) {
+            Ok(url) => panic!("{:?} was accepted as {}", input, url),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn adds_a_missing_scheme() {
+        for (input, expected) in [
+            ("example.com", "https://example.com/"),
+            ("example.com/a?b=c", "https://example.com/a?b=c"),
+            ("//example.com/a", "https://example.com/a"),
+            ("example.com:8080/a", "https://example.com:8080/a"),
+            ("HTTP://Example.com", "http://example.com/"),
+            ("  https://example.com/a \n", "https://example.com/a"),
+        ] {
+            assert_eq!(normalized(input), expected, "for {:?}", input);
+        }
+    }
+
+    #[test]
+    fn drops_wrappers_and_trailing_punctuation() {
+        for (input, expected) in [
+            ("<https://example.com/a>", "https://example.com/a"),
+            ("\"https://example.com/a\"", "https://example.com/a"),
+            ("'example.com/a'", "https://example.com/a"),
+            ("“https://example.com/a”", "https://example.com/a"),
+            ("https://example.com/a.", "https://example.com/a"),
+            ("https://example.com/a,", "https://example.com/a"),
+            ("https://example.com/a;", "https://example.com/a"),
+            ("(https://example.com/a)", "https://example.com/a"),
+            ("(see https://example.com/a).", "https://example.com/a"),
+            ("Read this: https://example.com/a!", "https://example.com/a"),
+            (
+                "https://en.wikipedia.org/wiki/Rust_(programming_language)",
+                "https://en.wikipedia.org/wiki/Rust_(programming_language)",
+            ),
+        ] {
+            assert_eq!(normalized(input), expected, "for {:?}", input);
+        }
+    }
+
+    #[test]
+    fn rejects_other_schemes() {
+        for input in [
+            "javascript:alert(1)",
+            "ftp://example.com/file",
+            "file:///etc/passwd",
+        ] {
+            assert!(
+                rejection(input).contains("not supported"),
+                "for {:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_hosts_without_a_dot() {
+        for input in [
+            "example",
+            "https://intranet/page",
+            "localhost:8080",
+            "example-",
+        ] {
+            assert!(
+                rejection(input).contains("not a full domain name"),
+                "for {:?}",
+                input
+            );
+        }
+        assert!(rejection("").contains("empty"));
+        assert!(rejection(" <> ").contains("empty"));
+    }
+
+    #[test]
+    fn accepts_ip_addresses() {
+        assert_eq!(normalized("192.168.1.1:8080"), "https://192.168.1.1:8080/");
+        assert_eq!(normalized("http://[::1]/a"), "http://[::1]/a");
+    }
+
+    #[test]
+    fn converts_international_domains_to_punycode() {
+        assert_eq!(normalized("bücher.de/a"), "https://xn--bcher-kva.de/a");
+        assert_eq!(normalized("https://例え.jp"), "https://xn--r8jz45g.jp/");
+    }
+}
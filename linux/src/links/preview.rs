@@ -0,0 +1,432 @@
+use crate::error::{AppError, Result};
+use reqwest::blocking::{Client as HttpClient, Response};
+use reqwest::header::CONTENT_TYPE;
+use std::io::Read;
+use std::time::Duration;
+use url::Url;
+
+/// Longest wait for a page or its icon, including redirects
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Bytes of a page read while looking for its title
+const MAX_PAGE_SIZE: u64 = 512 * 1024;
+
+/// Favicons larger than this are not kept
+const MAX_ICON_SIZE: u64 = 256 * 1024;
+
+/// Titles are cut to this many characters
+const MAX_TITLE_LEN: usize = 300;
+
+/// What a link's destination says about itself
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PageInfo {
+    /// Open Graph title, or the document title
+    pub title: Option<String>,
+    /// Icon declared by the page, or `/favicon.ico` of the site it ended on
+    pub icon_url: Option<Url>,
+}
+
+/// Looks up titles and favicons of shortened pages
+pub struct PageFetcher {
+    client: HttpClient,
+    max_page_size: u64,
+    max_icon_size: u64,
+}
+
+impl PageFetcher {
+    pub fn new() -> Result<Self> {
+        Self::with_limits(FETCH_TIMEOUT, MAX_PAGE_SIZE, MAX_ICON_SIZE)
+    }
+
+    pub fn with_limits(timeout: Duration, max_page_size: u64, max_icon_size: u64) -> Result<Self> {
+        let client = HttpClient::builder()
+            .timeout(timeout)
+            .user_agent(concat!("see/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e: reqwest::Error| AppError::Api(e.to_string()))?;
+        Ok(Self {
+            client,
+            max_page_size,
+            max_icon_size,
+        })
+    }
+
+    /// Fetch a page and read its title and icon from the head
+    pub fn fetch_page(&self, url: &Url) -> Result<PageInfo> {
+        let response = self.get(url)?;
+        let base = response.url().clone();
+
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let is_html = content_type.is_empty()
+            || content_type.starts_with("text/html")
+            || content_type.starts_with("application/xhtml+xml");
+        if !is_html {
+            return Ok(PageInfo {
+                title: None,
+                icon_url: base.join("/favicon.ico").ok(),
+            });
+        }
+
+        // A truncated page still has its head, so read up to the cap and stop
+        let mut bytes = Vec::new();
+        response.take(self.max_page_size).read_to_end(&mut bytes)?;
+        let html = decode_html(&bytes, &content_type);
+
+        let mut info = parse_head(&html, &base);
+        if info.icon_url.is_none() {
+            info.icon_url = base.join("/favicon.ico").ok();
+        }
+        Ok(info)
+    }
+
+    /// Download an icon, refusing anything over the size cap
+    pub fn fetch_icon(&self, url: &Url) -> Result<Vec<u8>> {
+        let response = self.get(url)?;
+        if response
+            .content_length()
+            .is_some_and(|len| len > self.max_icon_size)
+        {
+            return Err(AppError::Api("icon is too large".to_string()));
+        }
+
+        let mut bytes = Vec::new();
+        response
+            .take(self.max_icon_size + 1)
+            .read_to_end(&mut bytes)?;
+        if bytes.len() as u64 > self.max_icon_size {
+            return Err(AppError::Api("icon is too large".to_string()));
+        }
+        Ok(bytes)
+    }
+
+    fn get(&self, url: &Url) -> Result<Response> {
+        let response = self
+            .client
+            .get(url.as_str())
+            .send()
+            .map_err(|e: reqwest::Error| AppError::Api(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::Api(format!("HTTP error {}", response.status())));
+        }
+        Ok(response)
+    }
+}
+
+/// Decode a page from the charset in its header or `<meta>` tag, UTF-8 otherwise
+fn decode_html(bytes: &[u8], content_type: &str) -> String {
+    let label = charset_param(content_type).or_else(|| {
+        let head = &bytes[..bytes.len().min(1024)];
+        let head = String::from_utf8_lossy(head).to_ascii_lowercase();
+        let start = head.find("charset=")? + "charset=".len();
+        let label: String = head[start..]
+            .trim_start_matches(['"', '\''])
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':'))
+            .collect();
+        Some(label)
+    });
+
+    let encoding = label
+        .and_then(|l| encoding_rs::Encoding::for_label(l.as_bytes()))
+        .unwrap_or(encoding_rs::UTF_8);
+    let (text, _, _) = encoding.decode(bytes);
+    text.into_owned()
+}
+
+fn charset_param(content_type: &str) -> Option<String> {
+    content_type.split(';').find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        (name.trim() == "charset").then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+/// Read the title and icon from the tags before `<body>`
+fn parse_head(html: &str, base: &Url) -> PageInfo {
+    // ASCII lowercasing keeps byte offsets, so `lower` indexes `html` too
+    let lower = html.to_ascii_lowercase();
+    let mut title = None;
+    let mut og_title = None;
+    let mut icon: Option<(u8, String)> = None;
+
+    let mut pos = 0;
+    while let Some(offset) = lower[pos..].find('<') {
+        let start = pos + offset;
+        if lower[start..].starts_with("<!--") {
+            match lower[start..].find("-->") {
+                Some(end) => pos = start + end + 3,
+                None => break,
+            }
+            continue;
+        }
+
+        let Some(end) = tag_end(&lower, start) else {
+            break;
+        };
+        let tag = &html[start + 1..end];
+        pos = end + 1;
+
+        let name = tag
+            .split_ascii_whitespace()
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches('/')
+            .to_ascii_lowercase();
+        match name.as_str() {
+            "title" if title.is_none() => {
+                let close = lower[pos..]
+                    .find("</title")
+                    .map_or(lower.len(), |i| pos + i);
+                title = clean_text(&html[pos..close]);
+                pos = close;
+            }
+            "script" | "style" => {
+                let close = format!("</{}", name);
+                pos = lower[pos..].find(&close).map_or(lower.len(), |i| pos + i);
+            }
+            "meta" if og_title.is_none() => {
+                let attrs = attributes(tag);
+                let is_og_title = ["property", "name"].iter().any(|key| {
+                    attr(&attrs, key).is_some_and(|v| v.eq_ignore_ascii_case("og:title"))
+                });
+                if is_og_title {
+                    og_title = attr(&attrs, "content").and_then(clean_text);
+                }
+            }
+            "link" => {
+                let attrs = attributes(tag);
+                let rel = attr(&attrs, "rel").unwrap_or_default().to_ascii_lowercase();
+                let rank = if rel.split_whitespace().any(|r| r == "icon") {
+                    2
+                } else if rel.contains("apple-touch-icon") {
+                    1
+                } else {
+                    0
+                };
+                let better = icon.as_ref().is_none_or(|(best, _)| rank > *best);
+                let href = attr(&attrs, "href").filter(|h| !h.trim().is_empty());
+                if let Some(href) = href.filter(|_| rank > 0 && better) {
+                    icon = Some((rank, decode_entities(href.trim())));
+                }
+            }
+            "body" | "/head" => break,
+            _ => {}
+        }
+    }
+
+    PageInfo {
+        title: og_title.or(title),
+        icon_url: icon.and_then(|(_, href)| base.join(&href).ok()),
+    }
+}
+
+/// Index of the `>` closing a tag, skipping any inside quoted values
+fn tag_end(lower: &str, start: usize) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in lower[start..].char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '>') => return Some(start + i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Attributes of a tag as lowercase names and raw values
+fn attributes(tag: &str) -> Vec<(String, &str)> {
+    let mut attrs = Vec::new();
+    let mut rest = tag.trim_start_matches(|c: char| !c.is_ascii_whitespace());
+
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == '/');
+        if rest.is_empty() {
+            break;
+        }
+
+        let name_len = rest
+            .find(|c: char| c.is_ascii_whitespace() || c == '=' || c == '/')
+            .unwrap_or(rest.len());
+        let name = rest[..name_len].to_ascii_lowercase();
+        rest = rest[name_len..].trim_start();
+
+        let mut value = "";
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            if let Some(quote) = after.chars().next().filter(|c| matches!(c, '"' | '\'')) {
+                let inner = &after[1..];
+                let len = inner.find(quote).unwrap_or(inner.len());
+                value = &inner[..len];
+                rest = inner.get(len + 1..).unwrap_or_default();
+            } else {
+                let len = after
+                    .find(|c: char| c.is_ascii_whitespace())
+                    .unwrap_or(after.len());
+                value = &after[..len];
+                rest = &after[len..];
+            }
+        }
+        attrs.push((name, value));
+    }
+    attrs
+}
+
+fn attr<'a>(attrs: &[(String, &'a str)], name: &str) -> Option<&'a str> {
+    attrs.iter().find(|(n, _)| n == name).map(|(_, v)| *v)
+}
+
+/// Decode entities and collapse whitespace, `None` when nothing is left
+fn clean_text(raw: &str) -> Option<String> {
+    let text = decode_entities(raw)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if text.is_empty() {
+        return None;
+    }
+    Some(text.chars().take(MAX_TITLE_LEN).collect())
+}
+
+/// Decode the character references that show up in titles
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let reference = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .map(|end| &rest[1..end + 1]);
+        let ch = reference.and_then(|r| match r {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            _ => {
+                let number = r.strip_prefix('#')?;
+                let code = match number.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => number.parse().ok()?,
+                };
+                char::from_u32(code)
+            }
+        });
+
+        match (ch, reference) {
+            (Some(ch), Some(reference)) => {
+                decoded.push(ch);
+                rest = &rest[reference.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{StubRequest, StubResponse, StubServer};
+    use std::time::Instant;
+
+    fn page_server(response: StubResponse) -> StubServer {
+        StubServer::start(move |_: &StubRequest| Some(response.clone()))
+    }
+
+    fn fetch(fetcher: &PageFetcher, server: &StubServer) -> Result<PageInfo> {
+        fetcher.fetch_page(&Url::parse(&server.url("/page")).unwrap())
+    }
+
+    #[test]
+    fn open_graph_title_beats_document_title() {
+        let server = page_server(StubResponse::html(
+            r#"<html><head><title>Document</title>
+            <meta property="og:title" content="Open &amp; Graph"></head><body></body></html>"#,
+        ));
+
+        let info = fetch(&PageFetcher::new().unwrap(), &server).unwrap();
+        assert_eq!(info.title.as_deref(), Some("Open & Graph"));
+        assert_eq!(info.icon_url.unwrap().as_str(), server.url("/favicon.ico"));
+    }
+
+    #[test]
+    fn charset_comes_from_header() {
+        let server = page_server(
+            StubResponse::new(200)
+                .header("Content-Type", "text/html; charset=ISO-8859-1")
+                .body(&b"<title>Caf\xe9</title>"[..]),
+        );
+
+        let info = fetch(&PageFetcher::new().unwrap(), &server).unwrap();
+        assert_eq!(info.title.as_deref(), Some("Café"));
+    }
+
+    #[test]
+    fn charset_comes_from_meta_tag() {
+        let server = page_server(StubResponse::html(
+            &b"<head><meta charset=\"windows-1252\"><title>Caf\xe9</title></head>"[..],
+        ));
+
+        let info = fetch(&PageFetcher::new().unwrap(), &server).unwrap();
+        assert_eq!(info.title.as_deref(), Some("Café"));
+    }
+
+    #[test]
+    fn reads_no_further_than_page_size_cap() {
+        let html = format!(
+            r#"<head><title>Early</title>{}<meta property="og:title" content="Late"></head>"#,
+            " ".repeat(1000)
+        );
+        let server = page_server(StubResponse::html(html));
+        let fetcher = PageFetcher::with_limits(FETCH_TIMEOUT, 200, MAX_ICON_SIZE).unwrap();
+
+        let info = fetch(&fetcher, &server).unwrap();
+        assert_eq!(info.title.as_deref(), Some("Early"));
+    }
+
+    #[test]
+    fn rejects_icons_over_size_cap() {
+        let fetcher = PageFetcher::with_limits(FETCH_TIMEOUT, MAX_PAGE_SIZE, 100).unwrap();
+        let icon = |response: StubResponse| {
+            let server = page_server(response);
+            fetcher.fetch_icon(&Url::parse(&server.url("/favicon.ico")).unwrap())
+        };
+
+        let small = icon(StubResponse::new(200).body(vec![1; 100]));
+        assert_eq!(small.unwrap().len(), 100);
+
+        // Declared up front, and only found out while streaming
+        let declared = icon(StubResponse::new(200).body(vec![1; 200]));
+        assert!(declared.is_err());
+        let streamed = icon(StubResponse::new(200).body(vec![1; 200]).chunked());
+        assert!(streamed.is_err());
+    }
+
+    #[test]
+    fn gives_up_after_timeout() {
+        let server =
+            page_server(StubResponse::html("<title>Slow</title>").delay(Duration::from_secs(3)));
+        let fetcher =
+            PageFetcher::with_limits(Duration::from_millis(300), MAX_PAGE_SIZE, MAX_ICON_SIZE)
+                .unwrap();
+
+        let started = Instant::now();
+        assert!(fetch(&fetcher, &server).is_err());
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
+}
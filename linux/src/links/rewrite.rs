@@ -0,0 +1,175 @@
+use crate::config::Config;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Query parameters that only identify where a click came from
+const TRACKING_PARAMS: &[&str] = &[
+    "fbclid",
+    "gclid",
+    "gclsrc",
+    "dclid",
+    "gbraid",
+    "wbraid",
+    "msclkid",
+    "yclid",
+    "twclid",
+    "ttclid",
+    "li_fat_id",
+    "igshid",
+    "mc_cid",
+    "mc_eid",
+    "_hsenc",
+    "_hsmi",
+    "mkt_tok",
+    "oly_anon_id",
+    "oly_enc_id",
+    "vero_id",
+    "_ga",
+    "_gl",
+];
+
+/// Parameters starting with these are dropped as well
+const TRACKING_PREFIXES: &[&str] = &["utm_"];
+
+/// A find and replace applied to whole URLs, optionally limited to one site
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RewriteRule {
+    /// Host the rule applies to, including its subdomains; empty for every host
+    #[serde(default)]
+    pub host: String,
+    /// Regular expression matched against the URL
+    pub find: String,
+    /// Replacement, where `$1` or `${name}` insert captured groups
+    #[serde(default)]
+    pub replace: String,
+}
+
+/// Cleans URLs before they are shortened
+#[derive(Debug, Default)]
+pub struct UrlRewriter {
+    strip_tracking: bool,
+    rules: Vec<(String, Regex, String)>,
+}
+
+impl UrlRewriter {
+    /// Rewriter for the configured rules. Rules that do not compile are skipped.
+    pub fn from_config(config: &Config) -> Self {
+        let rules = config
+            .rewrite_rules()
+            .iter()
+            .filter_map(|rule| match Regex::new(&rule.find) {
+                Ok(regex) => Some((
+                    rule.host.trim().to_ascii_lowercase(),
+                    regex,
+                    rule.replace.clone(),
+                )),
+                Err(e) => {
+                    log::warn!("Skipping rewrite rule \"{}\": {}", rule.find, e);
+                    None
+                }
+            })
+            .collect();
+
+        Self {
+            strip_tracking: config.strip_tracking_params(),
+            rules,
+        }
+    }
+
+    /// URL with tracking parameters removed and the rules for its host applied
+    pub fn rewrite(&self, url: &Url) -> Url {
+        let mut url = if self.strip_tracking {
+            Self::strip_tracking(url)
+        } else {
+            url.clone()
+        };
+
+        for (host, regex, replace) in &self.rules {
+            if !host.is_empty() && !Self::host_matches(&url, host) {
+                continue;
+            }
+            let rewritten = regex.replace_all(url.as_str(), replace.as_str());
+            // A rule that produces something unusable is ignored rather than breaking the link
+            match Url::parse(&rewritten) {
+                Ok(rewritten) => url = rewritten,
+                Err(e) => log::warn!("Rewrite rule \"{}\" made an invalid URL: {}", regex, e),
+            }
+        }
+        url
+    }
+
+    fn strip_tracking(url: &Url) -> Url {
+        let is_tracking = |key: &str| {
+            let key = key.to_ascii_lowercase();
+            TRACKING_PARAMS.contains(&key.as_str())
+                || TRACKING_PREFIXES.iter().any(|p| key.starts_with(p))
+        };
+        let Some(query) = url.query() else {
+            return url.clone();
+        };
+
+        let kept = query_params(query, |key| !is_tracking(key));
+        if kept.len() == query_params(query, |_| true).len() {
+            return url.clone();
+        }
+
+        let mut stripped = url.clone();
+        stripped.set_query(Some(kept.join("&").as_str()).filter(|q| !q.is_empty()));
+        stripped
+    }
+
+    fn host_matches(url: &Url, host: &str) -> bool {
+        url.host_str().is_some_and(|h| {
+            let h = h.to_ascii_lowercase();
+            h == host || h.ends_with(&format!(".{}", host))
+        })
+    }
+}
+
+/// The `key=value` segments of a raw query whose decoded key passes `keep`.
+///
+/// Segments are returned as written, since re-encoding them as form data
+/// would turn `%20` into `+` and `flag` into `flag=` and break signed URLs.
+pub(super) fn query_params(query: &str, keep: impl Fn(&str) -> bool) -> Vec<&str> {
+    query
+        .split('&')
+        .filter(|segment| !segment.is_empty())
+        .filter(|segment| {
+            url::form_urlencoded::parse(segment.as_bytes())
+                .next()
+                .is_none_or(|(key, _)| keep(&key))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strip(url: &str) -> String {
+        UrlRewriter::strip_tracking(&Url::parse(url).unwrap()).to_string()
+    }
+
+    #[test]
+    fn strips_tracking_params_and_keeps_the_rest_as_written() {
+        assert_eq!(
+            strip("https://example.com/a?q=two%20words&utm_source=x&flag&sig=a+b%2F&fbclid=1"),
+            "https://example.com/a?q=two%20words&flag&sig=a+b%2F"
+        );
+    }
+
+    #[test]
+    fn drops_query_left_empty() {
+        assert_eq!(
+            strip("https://example.com/a?utm_medium=email&gclid=2#top"),
+            "https://example.com/a#top"
+        );
+    }
+
+    #[test]
+    fn leaves_clean_urls_alone() {
+        let url = "https://example.com/a?b=%7E&&c";
+        assert_eq!(strip(url), url);
+    }
+}
@@ -1,13 +1,23 @@
 mod api;
 mod application;
+mod cli;
 mod config;
+mod crypto;
 mod error;
+mod format;
+mod links;
+mod media;
 mod qrcode;
 mod storage;
+#[cfg(test)]
+mod testing;
+mod text;
 mod views;
 mod widgets;
 
 use application::SeeApplication;
+use cli::Invocation;
+use error::AppError;
 use gtk::{gio, glib, prelude::*};
 
 const APP_ID: &str = "ee.s.app";
@@ -15,6 +25,12 @@ const APP_ID: &str = "ee.s.app";
 fn main() -> glib::ExitCode {
     env_logger::init();
 
+    // Subcommands run in the terminal without a display, so GTK is never initialized
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if Invocation::is_command(&args) {
+        return run_command(&args);
+    }
+
     // Initialize GTK first
     gtk::init().expect("Failed to initialize GTK");
 
@@ -32,3 +48,17 @@ fn main() -> glib::ExitCode {
     let app = SeeApplication::new();
     app.run()
 }
+
+fn run_command(args: &[String]) -> glib::ExitCode {
+    match Invocation::parse(args).and_then(cli::run) {
+        Ok(()) => glib::ExitCode::SUCCESS,
+        Err(AppError::Usage(message)) => {
+            eprintln!("see: {}\nRun \"see help\" for usage.", message);
+            glib::ExitCode::FAILURE
+        }
+        Err(e) => {
+            eprintln!("see: {}", e);
+            glib::ExitCode::FAILURE
+        }
+    }
+}
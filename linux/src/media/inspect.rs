@@ -0,0 +1,315 @@
+use crate::error::Result;
+use image::metadata::Orientation;
+use image::{ImageDecoder, ImageReader};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// Bytes read from the start of a file to recognise its type
+const SNIFF_LEN: usize = 512;
+
+/// PDFs larger than this are not scanned for their page count
+const MAX_PDF_SCAN: u64 = 64 * 1024 * 1024;
+
+/// What is known about a file's content before it is uploaded
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileInfo {
+    #[serde(default)]
+    pub mime_type: Option<String>,
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub height: Option<u32>,
+    #[serde(default)]
+    pub page_count: Option<u32>,
+    /// Hex SHA-256 of the contents, used to spot duplicate uploads
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+pub struct FileInspector;
+
+impl FileInspector {
+    /// Detect the content type of a file and read its dimensions or page count
+    pub fn inspect(path: &Path) -> Result<FileInfo> {
+        let mut header = Vec::with_capacity(SNIFF_LEN);
+        File::open(path)?
+            .take(SNIFF_LEN as u64)
+            .read_to_end(&mut header)?;
+
+        let mut info = FileInfo {
+            mime_type: Self::sniff_mime(&header).map(String::from),
+            sha256: Some(Self::sha256(path)?),
+            ..Default::default()
+        };
+
+        match info.mime_type.as_deref() {
+            Some(mime) if mime.starts_with("image/") => {
+                if let Some((width, height)) = Self::image_dimensions(path) {
+                    info.width = Some(width);
+                    info.height = Some(height);
+                }
+            }
+            Some("application/pdf") => {
+                info.page_count = Self::pdf_page_count(path);
+            }
+            _ => {}
+        }
+
+        Ok(info)
+    }
+
+    /// Hex SHA-256 digest of a file's contents
+    pub fn sha256(path: &Path) -> Result<String> {
+        let mut hasher = Sha256::new();
+        io::copy(&mut File::open(path)?, &mut hasher)?;
+        Ok(hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect())
+    }
+
+    /// Whether a header starting with `BM` goes on with a BMP info header
+    /// rather than being text that happens to start with those letters
+    fn has_dib_header(header: &[u8]) -> bool {
+        header
+            .get(14..18)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .is_some_and(|size| matches!(size, 12 | 40 | 52 | 56 | 64 | 108 | 124))
+    }
+
+    /// Recognise a content type from the magic bytes at the start of a file
+    pub fn sniff_mime(header: &[u8]) -> Option<&'static str> {
+        let starts = |magic: &[u8]| header.starts_with(magic);
+        let at = |offset: usize, magic: &[u8]| {
+            header
+                .get(offset..offset + magic.len())
+                .is_some_and(|b| b == magic)
+        };
+
+        let mime = if starts(b"\x89PNG\r\n\x1a\n") {
+            "image/png"
+        } else if starts(b"\xff\xd8\xff") {
+            "image/jpeg"
+        } else if starts(b"GIF87a") || starts(b"GIF89a") {
+            "image/gif"
+        } else if starts(b"RIFF") && at(8, b"WEBP") {
+            "image/webp"
+        } else if starts(b"RIFF") && at(8, b"WAVE") {
+            "audio/wav"
+        } else if starts(b"RIFF") && at(8, b"AVI ") {
+            "video/x-msvideo"
+        } else if starts(b"BM") && Self::has_dib_header(header) {
+            "image/bmp"
+        } else if starts(b"II*\0") || starts(b"MM\0*") {
+            "image/tiff"
+        } else if starts(b"\0\0\x01\0") {
+            "image/x-icon"
+        } else if at(4, b"ftyp") {
+            match header.get(8..12) {
+                Some(b"avif") | Some(b"avis") => "image/avif",
+                Some(b"heic") | Some(b"heix") | Some(b"heim") | Some(b"heis")
+                | Some(b"mif1") | Some(b"msf1") => "image/heic",
+                Some(b"qt  ") => "video/quicktime",
+                Some(b"M4A ") => "audio/mp4",
+                _ => "video/mp4",
+            }
+        } else if starts(b"\x1a\x45\xdf\xa3") {
+            if header.windows(4).any(|w| w == b"webm") {
+                "video/webm"
+            } else {
+                "video/x-matroska"
+            }
+        } else if starts(b"ID3") || starts(b"\xff\xfb") || starts(b"\xff\xf3") || starts(b"\xff\xf2")
+        {
+            "audio/mpeg"
+        } else if starts(b"OggS") {
+            "audio/ogg"
+        } else if starts(b"fLaC") {
+            "audio/flac"
+        } else if starts(b"%PDF-") {
+            "application/pdf"
+        } else if starts(b"PK\x03\x04") {
+            "application/zip"
+        } else if starts(b"\x1f\x8b") {
+            "application/gzip"
+        } else if starts(b"7z\xbc\xaf\x27\x1c") {
+            "application/x-7z-compressed"
+        } else if starts(b"Rar!\x1a\x07") {
+            "application/vnd.rar"
+        } else if !header.is_empty() && !header.contains(&0) && Self::is_text(header) {
+            let lower = String::from_utf8_lossy(header).to_lowercase();
+            if lower.contains("<svg") {
+                "image/svg+xml"
+            } else {
+                "text/plain"
+            }
+        } else {
+            return None;
+        };
+        Some(mime)
+    }
+
+    /// Read image dimensions from the header, as displayed after EXIF rotation
+    fn image_dimensions(path: &Path) -> Option<(u32, u32)> {
+        let mut decoder = ImageReader::open(path)
+            .ok()?
+            .with_guessed_format()
+            .ok()?
+            .into_decoder()
+            .ok()?;
+        let (width, height) = decoder.dimensions();
+
+        match decoder.orientation() {
+            Ok(
+                Orientation::Rotate90
+                | Orientation::Rotate270
+                | Orientation::Rotate90FlipH
+                | Orientation::Rotate270FlipH,
+            ) => Some((height, width)),
+            _ => Some((width, height)),
+        }
+    }
+
+    /// Best-effort page count from the page objects in a PDF.
+    ///
+    /// Pages kept inside compressed object streams are not visible this way,
+    /// so fall back to the largest `/Count` of a page tree node.
+    fn pdf_page_count(path: &Path) -> Option<u32> {
+        let mut data = Vec::new();
+        File::open(path)
+            .ok()?
+            .take(MAX_PDF_SCAN)
+            .read_to_end(&mut data)
+            .ok()?;
+
+        let mut pages = 0;
+        for marker in [&b"/Type /Page"[..], &b"/Type/Page"[..]] {
+            pages += data
+                .windows(marker.len() + 1)
+                .filter(|w| w.starts_with(marker) && !w[marker.len()].is_ascii_alphabetic())
+                .count() as u32;
+        }
+        if pages > 0 {
+            return Some(pages);
+        }
+
+        data.windows(7)
+            .enumerate()
+            .filter(|(_, w)| *w == b"/Count ")
+            .filter_map(|(i, _)| {
+                let digits: String = data[i + 7..]
+                    .iter()
+                    .take_while(|b| b.is_ascii_digit())
+                    .map(|&b| b as char)
+                    .collect();
+                digits.parse::<u32>().ok()
+            })
+            .max()
+    }
+
+    /// Whether a header looks like UTF-8 text, allowing a character cut off at the end
+    fn is_text(header: &[u8]) -> bool {
+        match std::str::from_utf8(header) {
+            Ok(_) => true,
+            Err(e) => e.error_len().is_none(),
+        }
+    }
+}
+
+/// Short human readable name of a content type, like "PNG" or "PDF"
+pub fn mime_label(mime_type: &str) -> String {
+    let label = match mime_type {
+        "image/jpeg" => "JPEG",
+        "image/svg+xml" => "SVG",
+        "image/x-icon" => "ICO",
+        "audio/mpeg" => "MP3",
+        "audio/mp4" => "M4A",
+        "video/quicktime" => "MOV",
+        "video/x-matroska" => "MKV",
+        "video/x-msvideo" => "AVI",
+        "application/x-7z-compressed" => "7Z",
+        "application/vnd.rar" => "RAR",
+        "text/plain" => "Text",
+        _ => {
+            let subtype = mime_type.rsplit('/').next().unwrap_or(mime_type);
+            return subtype.trim_start_matches("x-").to_uppercase();
+        }
+    };
+    label.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::temp_dir;
+
+    #[test]
+    fn sniffs_formats_from_their_signatures() {
+        let mut bmp = b"BM\x46\0\0\0\0\0\0\0\x36\0\0\0".to_vec();
+        bmp.extend_from_slice(&40u32.to_le_bytes());
+
+        for (header, expected) in [
+            (&b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"[..], Some("image/png")),
+            (b"\xff\xd8\xff\xe0\0\x10JFIF\0", Some("image/jpeg")),
+            (b"GIF87a\x01\0\x01\0", Some("image/gif")),
+            (b"GIF89a\x01\0\x01\0", Some("image/gif")),
+            (b"RIFF\x24\0\0\0WEBPVP8 ", Some("image/webp")),
+            (b"RIFF\x24\0\0\0WAVEfmt ", Some("audio/wav")),
+            (&bmp, Some("image/bmp")),
+            (b"%PDF-1.7\n%\xe2\xe3\xcf\xd3", Some("application/pdf")),
+            (b"PK\x03\x04\x14\0\0\0\x08\0", Some("application/zip")),
+            (b"Hello, world\n", Some("text/plain")),
+            (b"\0\x01\x02\x03\xfe\xfd", None),
+            (b"", None),
+        ] {
+            assert_eq!(
+                FileInspector::sniff_mime(header),
+                expected,
+                "for {:?}",
+                header
+            );
+        }
+    }
+
+    #[test]
+    fn does_not_take_text_starting_with_bm_for_a_bitmap() {
+        assert_eq!(
+            FileInspector::sniff_mime(b"BMW service history, 2019 to 2024\n"),
+            Some("text/plain")
+        );
+    }
+
+    fn pdf_pages(name: &str, pdf: &[u8]) -> Option<u32> {
+        let path = temp_dir(name).join("document.pdf");
+        std::fs::write(&path, pdf).unwrap();
+        FileInspector::pdf_page_count(&path)
+    }
+
+    #[test]
+    fn counts_pdf_pages_but_not_page_trees() {
+        let pdf = b"%PDF-1.4
+1 0 obj << /Type /Catalog /Pages 2 0 R >> endobj
+2 0 obj << /Type /Pages /Kids [3 0 R 4 0 R 5 0 R] /Count 3 >> endobj
+3 0 obj << /Type /Page /Parent 2 0 R >> endobj
+4 0 obj << /Type/Page /Parent 2 0 R >> endobj
+5 0 obj << /Type /Page/Parent 2 0 R >> endobj
+trailer << /Root 1 0 R >>
+%%EOF";
+        assert_eq!(pdf_pages("pdf-pages", pdf), Some(3));
+    }
+
+    #[test]
+    fn falls_back_to_the_page_tree_count() {
+        // Page objects packed into a compressed object stream can't be seen
+        let pdf = b"%PDF-1.5
+1 0 obj << /Type /Pages /Kids [4 0 R 9 0 R] /Count 12 >> endobj
+4 0 obj << /Type /Pages /Parent 1 0 R /Count 5 >> endobj
+%%EOF";
+        assert_eq!(pdf_pages("pdf-count", pdf), Some(12));
+        assert_eq!(pdf_pages("pdf-none", b"%PDF-1.5\n%%EOF"), None);
+    }
+}
@@ -0,0 +1,240 @@
+use crate::error::{AppError, Result};
+
+const SOI: u8 = 0xd8;
+const EOI: u8 = 0xd9;
+const SOS: u8 = 0xda;
+const APP0: u8 = 0xe0;
+const APP1: u8 = 0xe1;
+const APP2: u8 = 0xe2;
+const APP14: u8 = 0xee;
+
+/// Copy of a JPEG without its EXIF, XMP, IPTC and comment segments, or
+/// anything stored after the image such as a second picture.
+///
+/// The compressed image data is copied unchanged, so nothing is lost to
+/// re-encoding. The ICC profile is kept so colours look the same, and an
+/// orientation other than upright is carried over in an EXIF segment that
+/// holds nothing else.
+pub fn strip_metadata(data: &[u8], orientation: u8) -> Result<Vec<u8>> {
+    if !data.starts_with(&[0xff, SOI]) {
+        return Err(invalid("missing start marker"));
+    }
+    let mut output = Vec::with_capacity(data.len());
+    output.extend_from_slice(&[0xff, SOI]);
+    let mut orientation_written = orientation <= 1;
+
+    let mut pos = 2;
+    loop {
+        if data.get(pos) != Some(&0xff) {
+            return Err(invalid("expected a marker"));
+        }
+        // Any number of 0xff bytes may pad a marker
+        while data.get(pos) == Some(&0xff) {
+            pos += 1;
+        }
+        let Some(&marker) = data.get(pos) else {
+            return Err(invalid("truncated"));
+        };
+        pos += 1;
+
+        match marker {
+            EOI => {
+                output.extend_from_slice(&[0xff, EOI]);
+                return Ok(output);
+            }
+            // Restart markers and TEM have no length
+            0xd0..=0xd7 | 0x01 => {
+                output.extend_from_slice(&[0xff, marker]);
+                continue;
+            }
+            _ => {}
+        }
+
+        let length = data
+            .get(pos..pos + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]) as usize)
+            .filter(|&length| length >= 2)
+            .ok_or_else(|| invalid("truncated"))?;
+        let segment = data
+            .get(pos..pos + length)
+            .ok_or_else(|| invalid("truncated"))?;
+        pos += length;
+
+        let payload = &segment[2..];
+        let keep = match marker {
+            APP0 => payload.starts_with(b"JFIF\0"),
+            APP2 => payload.starts_with(b"ICC_PROFILE\0"),
+            // Tells decoders how the colour channels were transformed
+            APP14 => payload.starts_with(b"Adobe"),
+            // EXIF, XMP, IPTC and other application data, and comments
+            0xe1..=0xef | 0xfe => false,
+            _ => true,
+        };
+        if !keep {
+            continue;
+        }
+
+        // The JFIF segment has to stay first
+        if !orientation_written && marker != APP0 {
+            output.extend_from_slice(&orientation_segment(orientation));
+            orientation_written = true;
+        }
+        output.extend_from_slice(&[0xff, marker]);
+        output.extend_from_slice(segment);
+
+        if marker == SOS {
+            // Compressed data runs until the next marker other than a
+            // stuffed 0xff 0x00 or a restart marker
+            let start = pos;
+            loop {
+                match data.get(pos..pos + 2) {
+                    Some(&[0xff, next]) if !matches!(next, 0x00 | 0xd0..=0xd7) => break,
+                    Some(_) => pos += 1,
+                    None => return Err(invalid("truncated")),
+                }
+            }
+            output.extend_from_slice(&data[start..pos]);
+        }
+    }
+}
+
+/// An APP1 segment with EXIF data holding only the orientation
+fn orientation_segment(orientation: u8) -> Vec<u8> {
+    let mut segment = vec![0xff, APP1, 0, 34];
+    segment.extend_from_slice(b"Exif\0\0");
+    // Big-endian TIFF header, with the first directory right after it
+    segment.extend_from_slice(b"MM\0\x2a\0\0\0\x08");
+    segment.extend_from_slice(&1u16.to_be_bytes());
+    // Tag 0x0112, one SHORT, padded to four bytes
+    segment.extend_from_slice(&[0x01, 0x12, 0, 3, 0, 0, 0, 1, 0, orientation, 0, 0]);
+    // No further directories
+    segment.extend_from_slice(&[0, 0, 0, 0]);
+    segment
+}
+
+fn invalid(reason: &str) -> AppError {
+    AppError::UnsupportedFile(format!("damaged JPEG: {}", reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::codecs::jpeg::JpegEncoder;
+    use image::metadata::Orientation;
+    use image::{ImageDecoder, ImageReader, RgbImage};
+    use std::io::Cursor;
+
+    fn segment(marker: u8, payload: &[u8]) -> Vec<u8> {
+        let mut segment = vec![0xff, marker];
+        segment.extend_from_slice(&(payload.len() as u16 + 2).to_be_bytes());
+        segment.extend_from_slice(payload);
+        segment
+    }
+
+    /// A small photo with every kind of metadata a camera or editor adds
+    fn photo_with_metadata() -> (Vec<u8>, Vec<u8>) {
+        let image = RgbImage::from_fn(16, 8, |x, y| image::Rgb([x as u8 * 16, y as u8 * 32, 90]));
+        let mut plain = Vec::new();
+        image
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut plain, 80))
+            .unwrap();
+
+        let mut exif = b"Exif\0\0MM\0\x2a\0\0\0\x08".to_vec();
+        exif.extend_from_slice(b"GPS 51.5074 N 0.1278 W");
+        let mut photo = plain[..2].to_vec();
+        photo.extend(segment(APP1, &exif));
+        photo.extend(segment(
+            APP1,
+            b"http://ns.adobe.com/xap/1.0/\0<x:xmpmeta>Author</x:xmpmeta>",
+        ));
+        photo.extend(segment(APP2, b"ICC_PROFILE\0\x01\x01sRGB profile"));
+        photo.extend(segment(0xed, b"Photoshop 3.0\0IPTC caption"));
+        photo.extend(segment(0xfe, b"Shot at home"));
+        photo.extend_from_slice(&plain[2..]);
+        photo.extend_from_slice(b"\xff\xd8 second picture with its own metadata");
+        (photo, plain)
+    }
+
+    fn contains(data: &[u8], needle: &[u8]) -> bool {
+        data.windows(needle.len()).any(|w| w == needle)
+    }
+
+    fn orientation(data: &[u8]) -> Orientation {
+        ImageReader::new(Cursor::new(data))
+            .with_guessed_format()
+            .unwrap()
+            .into_decoder()
+            .unwrap()
+            .orientation()
+            .unwrap()
+    }
+
+    #[test]
+    fn removes_metadata_and_keeps_the_pixels() {
+        let (photo, plain) = photo_with_metadata();
+        let stripped = strip_metadata(&photo, 1).unwrap();
+
+        for secret in [
+            &b"GPS"[..],
+            b"xmpmeta",
+            b"IPTC",
+            b"Shot at home",
+            b"second picture",
+        ] {
+            assert!(!contains(&stripped, secret), "kept {:?}", secret);
+        }
+        assert!(contains(&stripped, b"ICC_PROFILE\0\x01\x01sRGB profile"));
+        assert!(stripped.ends_with(&[0xff, EOI]));
+
+        // The compressed data is copied, not decoded and encoded again
+        let decoded = |data: &[u8]| image::load_from_memory(data).unwrap().to_rgb8();
+        assert_eq!(decoded(&stripped), decoded(&plain));
+        let scan = plain.windows(2).position(|w| w == [0xff, SOS]).unwrap();
+        assert!(stripped.ends_with(&plain[scan..]));
+    }
+
+    #[test]
+    fn keeps_only_the_orientation_from_exif() {
+        let (photo, _) = photo_with_metadata();
+
+        let upright = strip_metadata(&photo, 1).unwrap();
+        assert!(!contains(&upright, b"Exif"));
+        assert_eq!(orientation(&upright), Orientation::NoTransforms);
+
+        let rotated = strip_metadata(&photo, 6).unwrap();
+        assert_eq!(orientation(&rotated), Orientation::Rotate90);
+        assert!(!contains(&rotated, b"GPS"));
+    }
+
+    #[test]
+    fn copies_every_scan_of_a_progressive_jpeg() {
+        let mut jpeg = vec![0xff, SOI];
+        jpeg.extend(segment(APP0, b"JFIF\0\x01\x01\0\0\x01\0\x01\0\0"));
+        jpeg.extend(segment(SOS, b"\x01\x01\0\0\x3f\0"));
+        jpeg.extend_from_slice(b"\x12\xff\x00\x34\xff\xd0\x56");
+        jpeg.extend(segment(0xfe, b"between scans"));
+        jpeg.extend(segment(0xc4, b"\x00huffman"));
+        jpeg.extend(segment(SOS, b"\x01\x01\0\0\x3f\0"));
+        jpeg.extend_from_slice(b"\x78\xff\xff\xd9");
+
+        let stripped = strip_metadata(&jpeg, 3).unwrap();
+        let mut expected = vec![0xff, SOI];
+        expected.extend(segment(APP0, b"JFIF\0\x01\x01\0\0\x01\0\x01\0\0"));
+        expected.extend(orientation_segment(3));
+        expected.extend(segment(SOS, b"\x01\x01\0\0\x3f\0"));
+        expected.extend_from_slice(b"\x12\xff\x00\x34\xff\xd0\x56");
+        expected.extend(segment(0xc4, b"\x00huffman"));
+        expected.extend(segment(SOS, b"\x01\x01\0\0\x3f\0"));
+        expected.extend_from_slice(b"\x78\xff\xd9");
+        assert_eq!(stripped, expected);
+    }
+
+    #[test]
+    fn rejects_damaged_files() {
+        let (photo, _) = photo_with_metadata();
+        assert!(strip_metadata(b"not a jpeg", 1).is_err());
+        assert!(strip_metadata(&photo[..40], 1).is_err());
+        let scan = photo.windows(2).position(|w| w == [0xff, SOS]).unwrap();
+        assert!(strip_metadata(&photo[..scan + 20], 1).is_err());
+    }
+}
@@ -0,0 +1,76 @@
+use crate::config::Config;
+use crate::error::{AppError, Result};
+
+const MB: u64 = 1024 * 1024;
+
+/// Size and type restrictions checked before anything is uploaded
+#[derive(Debug, Clone)]
+pub struct UploadLimits {
+    /// Largest accepted file in bytes, 0 for no limit
+    pub max_size: u64,
+    pub allowed_types: Vec<String>,
+}
+
+impl UploadLimits {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_size: config.max_upload_size_mb().saturating_mul(MB),
+            allowed_types: config.allowed_upload_types().to_vec(),
+        }
+    }
+
+    pub fn check(&self, filename: &str, size: u64, mime_type: Option<&str>) -> Result<()> {
+        if self.max_size > 0 && size > self.max_size {
+            return Err(AppError::UploadRejected(format!(
+                "{} is {:.1} MB, the limit is {} MB",
+                filename,
+                size as f64 / MB as f64,
+                self.max_size / MB
+            )));
+        }
+
+        if !self.allowed_types.is_empty()
+            && !self
+                .allowed_types
+                .iter()
+                .any(|pattern| Self::matches(pattern, filename, mime_type))
+        {
+            return Err(AppError::UploadRejected(format!(
+                "{} is not an allowed file type ({})",
+                filename,
+                mime_type.unwrap_or("unknown type")
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Human readable size limit, for hints in the UI
+    pub fn describe(&self) -> String {
+        if self.max_size == 0 {
+            "No file size limit".to_string()
+        } else {
+            format!("Maximum file size: {} MB", self.max_size / MB)
+        }
+    }
+
+    fn matches(pattern: &str, filename: &str, mime_type: Option<&str>) -> bool {
+        let pattern = pattern.trim().to_lowercase();
+
+        if let Some(extension) = pattern.strip_prefix('.') {
+            return filename
+                .rsplit_once('.')
+                .is_some_and(|(_, ext)| ext.eq_ignore_ascii_case(extension));
+        }
+
+        let Some(mime_type) = mime_type else {
+            return false;
+        };
+        match pattern.strip_suffix("/*") {
+            Some(prefix) => mime_type
+                .split_once('/')
+                .is_some_and(|(kind, _)| kind == prefix),
+            None => mime_type == pattern,
+        }
+    }
+}
@@ -0,0 +1,11 @@
+mod inspect;
+mod jpeg;
+mod limits;
+mod processor;
+pub mod staging;
+mod thumbnails;
+
+pub use inspect::{mime_label, FileInfo, FileInspector};
+pub use limits::UploadLimits;
+pub use processor::{ImageOptions, ImageOutputFormat, ImageProcessor};
+pub use thumbnails::{ThumbnailCache, MAX_THUMBNAIL_SOURCE_SIZE};
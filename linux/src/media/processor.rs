@@ -0,0 +1,245 @@
+use crate::config::Config;
+use crate::error::{AppError, Result};
+use crate::media::{jpeg, staging};
+use image::codecs::gif::GifDecoder;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::{PngDecoder, PngEncoder};
+use image::codecs::webp::{WebPDecoder, WebPEncoder};
+use image::imageops::FilterType;
+use image::{AnimationDecoder, DynamicImage, ImageDecoder, ImageError, ImageFormat, ImageReader};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+/// Output format for pasted and converted images
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageOutputFormat {
+    #[default]
+    Png,
+    Webp,
+    Jpeg,
+}
+
+impl ImageOutputFormat {
+    pub fn image_format(&self) -> ImageFormat {
+        match self {
+            ImageOutputFormat::Png => ImageFormat::Png,
+            ImageOutputFormat::Webp => ImageFormat::WebP,
+            ImageOutputFormat::Jpeg => ImageFormat::Jpeg,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageOutputFormat::Png => "png",
+            ImageOutputFormat::Webp => "webp",
+            ImageOutputFormat::Jpeg => "jpg",
+        }
+    }
+}
+
+/// Resize and re-encode settings applied before upload
+#[derive(Debug, Clone, Copy)]
+pub struct ImageOptions {
+    pub format: ImageOutputFormat,
+    pub quality: u8,
+    /// Longest allowed edge in pixels, 0 for no limit
+    pub max_dimension: u32,
+}
+
+impl ImageOptions {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            format: config.clipboard_image_format(),
+            quality: config.image_quality(),
+            max_dimension: config.image_max_dimension(),
+        }
+    }
+}
+
+pub struct ImageProcessor;
+
+impl ImageProcessor {
+    /// Detect the image format of a file from its contents
+    pub fn detect_format(path: &Path) -> Option<ImageFormat> {
+        ImageReader::open(path)
+            .ok()?
+            .with_guessed_format()
+            .ok()?
+            .format()
+    }
+
+    /// Copy a JPEG, PNG or WebP image without EXIF, GPS or XMP metadata.
+    ///
+    /// JPEGs keep their compressed data, ICC profile and orientation. PNG and
+    /// WebP images are decoded, turned upright and written losslessly. Returns
+    /// the path of a staged copy with the same filename, or `None` when the
+    /// file is not a supported still image.
+    pub fn strip_metadata(path: &Path) -> Result<Option<PathBuf>> {
+        let format = match Self::detect_format(path) {
+            Some(format @ (ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::WebP)) => format,
+            _ => return Ok(None),
+        };
+
+        if Self::is_animated(path, format)? {
+            log::warn!(
+                "Skipping metadata removal for animated image: {}",
+                path.display()
+            );
+            return Ok(None);
+        }
+
+        let filename = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| AppError::Storage("Invalid file name".to_string()))?;
+        let output_path = staging::staging_path(filename)?;
+
+        if format == ImageFormat::Jpeg {
+            let orientation = ImageReader::open(path)?
+                .with_guessed_format()?
+                .into_decoder()?
+                .orientation()?;
+            let stripped = jpeg::strip_metadata(&fs::read(path)?, orientation.to_exif())?;
+            fs::write(&output_path, stripped)?;
+        } else {
+            // Both are encoded losslessly, so the quality is not used
+            let image = Self::decode_oriented(path)?;
+            Self::encode(&image, format, 100, &output_path)?;
+        }
+        Ok(Some(output_path))
+    }
+
+    /// Encode a pasted image in the configured format, downscaling it if needed.
+    ///
+    /// `stem` is the filename without extension; the extension follows the format.
+    pub fn process_bytes(bytes: &[u8], stem: &str, options: &ImageOptions) -> Result<PathBuf> {
+        let image = Self::resize(image::load_from_memory(bytes)?, options.max_dimension);
+
+        let output_path =
+            staging::staging_path(&format!("{}.{}", stem, options.format.extension()))?;
+        Self::encode(
+            &image,
+            options.format.image_format(),
+            options.quality,
+            &output_path,
+        )?;
+        Ok(output_path)
+    }
+
+    /// Resize and recompress an image file before upload.
+    ///
+    /// JPEG, PNG and WebP keep their format; anything else the decoder can read,
+    /// such as AVIF when built with a decoder for it, is converted to the
+    /// configured format. Returns `None` for files that are not decodable still
+    /// images so they can be uploaded unchanged.
+    pub fn process_file(path: &Path, options: &ImageOptions) -> Result<Option<PathBuf>> {
+        let Some(source_format) = Self::detect_format(path) else {
+            return Ok(None);
+        };
+
+        if Self::is_animated(path, source_format)? {
+            return Ok(None);
+        }
+
+        let image = match Self::decode_oriented(path) {
+            Ok(image) => image,
+            Err(AppError::Image(ImageError::Unsupported(e))) => {
+                log::info!("Uploading {} unchanged: {}", path.display(), e);
+                return Ok(None);
+            }
+            Err(e) => return Err(e),
+        };
+        let image = Self::resize(image, options.max_dimension);
+
+        let (format, extension) = match source_format {
+            ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::WebP => (
+                source_format,
+                path.extension()
+                    .and_then(|e| e.to_str())
+                    .unwrap_or(options.format.extension()),
+            ),
+            _ => (options.format.image_format(), options.format.extension()),
+        };
+
+        let stem = path
+            .file_stem()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| AppError::Storage("Invalid file name".to_string()))?;
+        let output_path = staging::staging_path(&format!("{}.{}", stem, extension))?;
+
+        Self::encode(&image, format, options.quality, &output_path)?;
+        Ok(Some(output_path))
+    }
+
+    /// Downscale an image so its longest edge fits within `max_dimension`
+    pub fn resize(image: DynamicImage, max_dimension: u32) -> DynamicImage {
+        if max_dimension == 0 || (image.width() <= max_dimension && image.height() <= max_dimension)
+        {
+            return image;
+        }
+        image.resize(max_dimension, max_dimension, FilterType::Lanczos3)
+    }
+
+    /// Decode an image and apply its EXIF orientation to the pixels
+    pub fn decode_oriented(path: &Path) -> Result<DynamicImage> {
+        let mut decoder = ImageReader::open(path)?
+            .with_guessed_format()?
+            .into_decoder()?;
+        let orientation = decoder.orientation()?;
+        let mut image = DynamicImage::from_decoder(decoder)?;
+        image.apply_orientation(orientation);
+        Ok(image)
+    }
+
+    /// Encode an image without any ancillary metadata.
+    ///
+    /// WebP output is lossless since the `image` crate has no lossy WebP encoder.
+    pub fn encode(
+        image: &DynamicImage,
+        format: ImageFormat,
+        quality: u8,
+        path: &Path,
+    ) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        match format {
+            ImageFormat::Jpeg => {
+                // JPEG has no alpha channel
+                let rgb = image.to_rgb8();
+                let encoder = JpegEncoder::new_with_quality(writer, quality);
+                rgb.write_with_encoder(encoder)?;
+            }
+            ImageFormat::Png => {
+                image.write_with_encoder(PngEncoder::new(writer))?;
+            }
+            ImageFormat::WebP => {
+                let rgba = image.to_rgba8();
+                rgba.write_with_encoder(WebPEncoder::new_lossless(writer))?;
+            }
+            other => {
+                image.write_to(&mut writer, other)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether the file has more than one frame or page, which re-encoding
+    /// would reduce to the first one
+    fn is_animated(path: &Path, format: ImageFormat) -> Result<bool> {
+        let reader = BufReader::new(File::open(path)?);
+        let animated = match format {
+            ImageFormat::Png => PngDecoder::new(reader)?.is_apng()?,
+            ImageFormat::WebP => WebPDecoder::new(reader)?.has_animation(),
+            ImageFormat::Gif => GifDecoder::new(reader)?.into_frames().take(2).count() > 1,
+            ImageFormat::Tiff => tiff::decoder::Decoder::new(reader)
+                .map_err(|e| AppError::UnsupportedFile(e.to_string()))?
+                .more_images(),
+            _ => false,
+        };
+        Ok(animated)
+    }
+}
@@ -0,0 +1,80 @@
+use crate::config::Config;
+use crate::error::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+static STAGING_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Staged copies without a checkpoint are left alone this long, in case
+/// another instance is still uploading them
+const STALE_AFTER: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Allocate a path for a rewritten upload that keeps the original filename.
+///
+/// Each call gets its own directory, since the filename is what the server
+/// records for the upload. These live in the data dir rather than the system
+/// temp dir so a chunked upload of the copy can still resume after a reboot.
+pub fn staging_path(filename: &str) -> Result<PathBuf> {
+    let dir = staging_dir()?.join(format!(
+        "{}-{}-{}",
+        std::process::id(),
+        chrono::Utc::now().timestamp_millis(),
+        STAGING_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::create_dir_all(&dir)?;
+    Ok(dir.join(filename))
+}
+
+/// Stage a file under another name, linking it when possible to avoid a copy
+pub fn stage_as(path: &Path, filename: &str) -> Result<PathBuf> {
+    let staged = staging_path(filename)?;
+    if fs::hard_link(path, &staged).is_err() {
+        fs::copy(path, &staged)?;
+    }
+    Ok(staged)
+}
+
+/// Remove a staged file and its directory once the upload has finished
+pub fn discard(path: &Path) {
+    if let Some(dir) = path.parent() {
+        if let Err(e) = fs::remove_dir_all(dir) {
+            log::warn!("Failed to remove staged upload {}: {}", dir.display(), e);
+        }
+    }
+}
+
+/// Remove staged copies left behind by uploads that never finished, except
+/// those a pending upload checkpoint still points to
+pub fn remove_stale(in_use: &[PathBuf]) {
+    let Ok(dir) = staging_dir() else {
+        return;
+    };
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let dir = entry.path();
+        if in_use.iter().any(|path| path.starts_with(&dir)) {
+            continue;
+        }
+        let stale = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > STALE_AFTER);
+        if !stale {
+            continue;
+        }
+        if let Err(e) = fs::remove_dir_all(&dir) {
+            log::warn!("Failed to remove staged upload {}: {}", dir.display(), e);
+        }
+    }
+}
+
+fn staging_dir() -> Result<PathBuf> {
+    Ok(Config::data_dir()?.join("uploads").join("staged"))
+}
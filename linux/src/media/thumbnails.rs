@@ -0,0 +1,93 @@
+use crate::config::Config;
+use crate::error::Result;
+use crate::media::ImageProcessor;
+use image::{DynamicImage, ImageFormat};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Longest edge of a cached thumbnail in pixels
+const THUMBNAIL_SIZE: u32 = 256;
+
+/// Files larger than this are not downloaded just to draw a thumbnail
+pub const MAX_THUMBNAIL_SOURCE_SIZE: u64 = 20 * 1024 * 1024;
+
+/// PNG thumbnails of uploaded images, keyed by file URL
+#[derive(Debug)]
+pub struct ThumbnailCache {
+    dir: PathBuf,
+}
+
+impl ThumbnailCache {
+    pub fn load() -> Result<Self> {
+        let dir = Config::data_dir()?.join("thumbnails");
+        Ok(Self { dir })
+    }
+
+    /// Whether a thumbnail can be drawn for a file of this type
+    pub fn supports(mime_type: Option<&str>, filename: &str) -> bool {
+        let format = match mime_type {
+            Some(mime) => ImageFormat::from_mime_type(mime),
+            None => ImageFormat::from_path(filename).ok(),
+        };
+        format.is_some_and(|f| f.reading_enabled())
+    }
+
+    /// Path of the cached thumbnail for a file, if there is one
+    pub fn get(&self, url: &str) -> Option<PathBuf> {
+        let path = self.thumbnail_path(url);
+        path.exists().then_some(path)
+    }
+
+    pub fn generate_from_file(&self, url: &str, source: &Path) -> Result<PathBuf> {
+        let image = ImageProcessor::decode_oriented(source)?;
+        self.save(url, &image)
+    }
+
+    pub fn generate_from_bytes(&self, url: &str, bytes: &[u8]) -> Result<PathBuf> {
+        let image = image::load_from_memory(bytes)?;
+        self.save(url, &image)
+    }
+
+    /// Total size of the cached thumbnails in bytes
+    pub fn size(&self) -> u64 {
+        fs::read_dir(&self.dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter_map(|e| e.metadata().ok())
+                    .map(|m| m.len())
+                    .sum()
+            })
+            .unwrap_or(0)
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        if self.dir.exists() {
+            fs::remove_dir_all(&self.dir)?;
+        }
+        Ok(())
+    }
+
+    fn save(&self, url: &str, image: &DynamicImage) -> Result<PathBuf> {
+        fs::create_dir_all(&self.dir)?;
+        let thumbnail = image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE);
+
+        // Write under a temporary name so a half-written file is never picked up
+        let path = self.thumbnail_path(url);
+        let partial = path.with_extension("part");
+        thumbnail.save_with_format(&partial, ImageFormat::Png)?;
+        fs::rename(&partial, &path)?;
+        Ok(path)
+    }
+
+    fn thumbnail_path(&self, url: &str) -> PathBuf {
+        self.dir.join(format!("{:016x}.png", fnv1a(url.as_bytes())))
+    }
+}
+
+/// Stable 64-bit FNV-1a hash, used for cache file names
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
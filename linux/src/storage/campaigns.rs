@@ -0,0 +1,64 @@
+use crate::config::Config;
+use crate::error::Result;
+use crate::links::Campaign;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// Campaign parameters saved under a name for reuse in the links form
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CampaignPreset {
+    pub name: String,
+    #[serde(flatten)]
+    pub campaign: Campaign,
+}
+
+#[derive(Debug)]
+pub struct CampaignPresets {
+    path: PathBuf,
+    presets: Vec<CampaignPreset>,
+}
+
+impl CampaignPresets {
+    pub fn load() -> Result<Self> {
+        let path = Config::data_dir()?.join("campaigns.json");
+        let presets = if path.exists() {
+            let content = fs::read_to_string(&path)?;
+            serde_json::from_str(&content)?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self { path, presets })
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(&self.presets)?)?;
+        Ok(())
+    }
+
+    pub fn presets(&self) -> &[CampaignPreset] {
+        &self.presets
+    }
+
+    /// Save a campaign under a name, replacing any preset already called that
+    pub fn set(&mut self, name: &str, campaign: Campaign) -> Result<()> {
+        let preset = CampaignPreset {
+            name: name.to_string(),
+            campaign,
+        };
+        match self.presets.iter_mut().find(|p| p.name == name) {
+            Some(existing) => *existing = preset,
+            None => self.presets.push(preset),
+        }
+        self.save()
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<()> {
+        self.presets.retain(|p| p.name != name);
+        self.save()
+    }
+}
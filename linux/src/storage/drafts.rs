@@ -0,0 +1,233 @@
+use crate::api::TextType;
+use crate::config::Config;
+use crate::error::{AppError, Result};
+use crate::links::Campaign;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// Unsent contents of the link form
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LinkDraft {
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub slug: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub domain: Option<String>,
+    /// Campaign fields, when the campaign section is turned on
+    #[serde(default)]
+    pub campaign: Option<Campaign>,
+}
+
+impl LinkDraft {
+    pub fn is_empty(&self) -> bool {
+        self.url.is_empty()
+            && self.slug.is_empty()
+            && self.title.is_empty()
+            && self.campaign.as_ref().is_none_or(Campaign::is_empty)
+    }
+
+    pub fn load() -> Result<Option<Self>> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path)?;
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+
+    /// Persist the draft, or forget it once the form is empty
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        if self.is_empty() {
+            if path.exists() {
+                fs::remove_file(&path)?;
+            }
+            return Ok(());
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    fn path() -> Result<PathBuf> {
+        Ok(Config::data_dir()?.join("drafts").join("link.json"))
+    }
+}
+
+/// Unsent contents of the text editor, saved under a name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextDraft {
+    pub name: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub content: String,
+    #[serde(default)]
+    pub text_type: TextType,
+    #[serde(default)]
+    pub syntax: Option<String>,
+    #[serde(default)]
+    pub domain: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TextDraft {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            title: String::new(),
+            content: String::new(),
+            text_type: TextType::default(),
+            syntax: None,
+            domain: None,
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TextDraftList {
+    #[serde(default)]
+    drafts: Vec<TextDraft>,
+    /// Name of the draft open in the editor
+    #[serde(default)]
+    active: Option<String>,
+}
+
+/// Named text drafts, one of which is open in the editor at a time
+#[derive(Debug)]
+pub struct TextDrafts {
+    path: PathBuf,
+    list: TextDraftList,
+}
+
+impl TextDrafts {
+    /// Load the saved drafts, starting with an empty one if there are none
+    pub fn load() -> Result<Self> {
+        let path = Config::data_dir()?.join("drafts").join("texts.json");
+        let list = if path.exists() {
+            let content = fs::read_to_string(&path)?;
+            serde_json::from_str(&content)?
+        } else {
+            TextDraftList::default()
+        };
+
+        let mut drafts = Self { path, list };
+        if drafts.list.drafts.is_empty() {
+            let name = drafts.unique_name("Draft");
+            drafts.list.drafts.push(TextDraft::new(name));
+        }
+        if drafts.active().is_none() {
+            drafts.list.active = Some(drafts.list.drafts[0].name.clone());
+        }
+        Ok(drafts)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(&self.list)?)?;
+        Ok(())
+    }
+
+    pub fn drafts(&self) -> &[TextDraft] {
+        &self.list.drafts
+    }
+
+    pub fn active(&self) -> Option<&TextDraft> {
+        let name = self.list.active.as_deref()?;
+        self.list.drafts.iter().find(|d| d.name == name)
+    }
+
+    pub fn set_active(&mut self, name: &str) -> Result<()> {
+        if self.list.drafts.iter().any(|d| d.name == name) {
+            self.list.active = Some(name.to_string());
+        }
+        self.save()
+    }
+
+    /// Store the editor contents into the active draft
+    pub fn update_active(&mut self, mut draft: TextDraft) -> Result<()> {
+        let Some(name) = self.list.active.clone() else {
+            return Ok(());
+        };
+        if let Some(existing) = self.list.drafts.iter_mut().find(|d| d.name == name) {
+            if existing.title == draft.title
+                && existing.content == draft.content
+                && existing.text_type == draft.text_type
+                && existing.syntax == draft.syntax
+                && existing.domain == draft.domain
+            {
+                return Ok(());
+            }
+            draft.name = name;
+            draft.updated_at = Utc::now();
+            *existing = draft;
+        }
+        self.save()
+    }
+
+    /// Add an empty draft and make it active
+    pub fn create(&mut self, name: &str) -> Result<()> {
+        self.check_name_free(name)?;
+        self.list.drafts.push(TextDraft::new(name.to_string()));
+        self.list.active = Some(name.to_string());
+        self.save()
+    }
+
+    pub fn rename_active(&mut self, name: &str) -> Result<()> {
+        let Some(current) = self.list.active.clone() else {
+            return Ok(());
+        };
+        if current == name {
+            return Ok(());
+        }
+
+        self.check_name_free(name)?;
+        if let Some(draft) = self.list.drafts.iter_mut().find(|d| d.name == current) {
+            draft.name = name.to_string();
+        }
+        self.list.active = Some(name.to_string());
+        self.save()
+    }
+
+    /// Remove the active draft and open the next one, keeping at least one draft around
+    pub fn remove_active(&mut self) -> Result<()> {
+        if let Some(name) = self.list.active.take() {
+            self.list.drafts.retain(|d| d.name != name);
+        }
+        if self.list.drafts.is_empty() {
+            let name = self.unique_name("Draft");
+            self.list.drafts.push(TextDraft::new(name));
+        }
+        self.list.active = Some(self.list.drafts[0].name.clone());
+        self.save()
+    }
+
+    /// `base` followed by the first free number, like "Draft 2"
+    pub fn unique_name(&self, base: &str) -> String {
+        (1..)
+            .map(|n| format!("{} {}", base, n))
+            .find(|name| !self.list.drafts.iter().any(|d| &d.name == name))
+            .unwrap_or_else(|| base.to_string())
+    }
+
+    fn check_name_free(&self, name: &str) -> Result<()> {
+        if self.list.drafts.iter().any(|d| d.name == name) {
+            return Err(AppError::Storage(format!(
+                "A draft named \"{}\" already exists",
+                name
+            )));
+        }
+        Ok(())
+    }
+}
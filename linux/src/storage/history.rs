@@ -1,9 +1,11 @@
 use crate::config::Config;
 use crate::error::Result;
+use crate::links::UrlNormalizer;
 use crate::storage::models::{FileEntry, LinkEntry, TextEntry};
 use serde::{Deserialize, Serialize};
-use std::fs;
-use std::path::PathBuf;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct History {
@@ -20,14 +22,12 @@ pub struct HistoryStorage {
 
 impl HistoryStorage {
     pub fn load() -> Result<Self> {
-        let path = Self::history_file_path()?;
-        let history = if path.exists() {
-            let content = fs::read_to_string(&path)?;
-            serde_json::from_str(&content)?
-        } else {
-            History::default()
-        };
+        Self::in_file(Self::history_file_path()?)
+    }
 
+    /// History kept in a file of its own
+    pub fn in_file(path: PathBuf) -> Result<Self> {
+        let history = Self::read(&path)?;
         Ok(Self { path, history })
     }
 
@@ -36,75 +36,180 @@ impl HistoryStorage {
         Ok(data_dir.join("history.json"))
     }
 
-    pub fn save(&self) -> Result<()> {
+    fn read(path: &Path) -> Result<History> {
+        if !path.exists() {
+            return Ok(History::default());
+        }
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Apply a change to the history on disk rather than to this copy, so
+    /// entries another window or the command line added or removed since it
+    /// was loaded are kept
+    fn update(&mut self, change: impl FnOnce(&mut History)) -> Result<()> {
         if let Some(parent) = self.path.parent() {
             fs::create_dir_all(parent)?;
         }
-        let content = serde_json::to_string_pretty(&self.history)?;
-        fs::write(&self.path, content)?;
+        // Held until the new file is in place; the lock goes with the handle
+        let lock = File::create(self.path.with_extension("lock"))?;
+        lock.lock()?;
+
+        let mut history = Self::read(&self.path)?;
+        change(&mut history);
+
+        // Readers see either the old file or the new one, never half of it
+        let temp = self.path.with_extension("json.tmp");
+        let mut file = File::create(&temp)?;
+        file.write_all(serde_json::to_string_pretty(&history)?.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temp, &self.path)?;
+
+        self.history = history;
         Ok(())
     }
 
     // Links
     pub fn add_link(&mut self, entry: LinkEntry) -> Result<()> {
-        self.history.links.insert(0, entry);
-        self.save()
+        self.update(|history| history.links.insert(0, entry))
     }
 
     pub fn remove_link(&mut self, domain: &str, slug: &str) -> Result<()> {
-        self.history
-            .links
-            .retain(|l| !(l.domain == domain && l.slug == slug));
-        self.save()
+        self.update(|history| {
+            history
+                .links
+                .retain(|l| !(l.domain == domain && l.slug == slug))
+        })
+    }
+
+    /// Give a link the title of the page it points to
+    pub fn set_link_title(&mut self, domain: &str, slug: &str, title: &str) -> Result<()> {
+        self.update(|history| {
+            if let Some(link) = history
+                .links
+                .iter_mut()
+                .find(|l| l.domain == domain && l.slug == slug)
+            {
+                link.title = Some(title.to_string());
+            }
+        })
+    }
+
+    /// Most recent link to the same URL on a domain, compared once normalized
+    pub fn find_link(&self, url: &str, domain: &str) -> Option<&LinkEntry> {
+        let url = UrlNormalizer::normalize(url).ok()?;
+        self.history.links.iter().find(|l| {
+            l.domain == domain
+                && UrlNormalizer::normalize(&l.original_url).is_ok_and(|u| u == url)
+        })
     }
 
     pub fn links(&self) -> &[LinkEntry] {
         &self.history.links
     }
 
-    pub fn clear_links(&mut self) {
-        self.history.links.clear();
+    pub fn clear_links(&mut self) -> Result<()> {
+        self.update(|history| history.links.clear())
     }
 
     // Texts
     pub fn add_text(&mut self, entry: TextEntry) -> Result<()> {
-        self.history.texts.insert(0, entry);
-        self.save()
+        self.update(|history| history.texts.insert(0, entry))
     }
 
     pub fn remove_text(&mut self, domain: &str, slug: &str) -> Result<()> {
-        self.history
-            .texts
-            .retain(|t| !(t.domain == domain && t.slug == slug));
-        self.save()
+        self.update(|history| {
+            history
+                .texts
+                .retain(|t| !(t.domain == domain && t.slug == slug))
+        })
     }
 
     pub fn texts(&self) -> &[TextEntry] {
         &self.history.texts
     }
 
-    pub fn clear_texts(&mut self) {
-        self.history.texts.clear();
+    pub fn clear_texts(&mut self) -> Result<()> {
+        self.update(|history| history.texts.clear())
     }
 
     // Files
     pub fn add_file(&mut self, entry: FileEntry) -> Result<()> {
-        self.history.files.insert(0, entry);
-        self.save()
+        self.update(|history| history.files.insert(0, entry))
     }
 
     pub fn remove_file(&mut self, domain: &str, slug: &str) -> Result<()> {
-        self.history
-            .files
-            .retain(|f| !(f.domain == domain && f.slug == slug));
-        self.save()
+        self.update(|history| {
+            history
+                .files
+                .retain(|f| !(f.domain == domain && f.slug == slug))
+        })
     }
 
     pub fn files(&self) -> &[FileEntry] {
         &self.history.files
     }
 
-    pub fn clear_files(&mut self) {
-        self.history.files.clear();
+    /// Most recent upload of the same contents, encrypted or not as requested
+    pub fn find_file_by_hash(&self, sha256: &str, encrypted: bool) -> Option<&FileEntry> {
+        self.history.files.iter().find(|f| {
+            f.sha256.as_deref() == Some(sha256) && f.encryption_key.is_some() == encrypted
+        })
+    }
+
+    pub fn clear_files(&mut self) -> Result<()> {
+        self.update(|history| history.files.clear())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::temp_dir;
+
+    fn link(slug: &str) -> LinkEntry {
+        LinkEntry::new(
+            format!("https://example.com/{}", slug),
+            format!("https://s.ee/{}", slug),
+            "s.ee".to_string(),
+            slug.to_string(),
+            None,
+        )
+    }
+
+    fn slugs(storage: &HistoryStorage) -> Vec<&str> {
+        storage.links().iter().map(|l| l.slug.as_str()).collect()
+    }
+
+    #[test]
+    fn keeps_links_added_by_another_instance() {
+        let path = temp_dir("history-add").join("history.json");
+        let mut window = HistoryStorage::in_file(path.clone()).unwrap();
+        let mut cli = HistoryStorage::in_file(path.clone()).unwrap();
+
+        window.add_link(link("a")).unwrap();
+        cli.add_link(link("b")).unwrap();
+        window.add_link(link("c")).unwrap();
+
+        assert_eq!(slugs(&window), ["c", "b", "a"]);
+        let reloaded = HistoryStorage::in_file(path).unwrap();
+        assert_eq!(slugs(&reloaded), ["c", "b", "a"]);
+    }
+
+    #[test]
+    fn does_not_bring_back_links_removed_by_another_instance() {
+        let path = temp_dir("history-remove").join("history.json");
+        let mut window = HistoryStorage::in_file(path.clone()).unwrap();
+        window.add_link(link("a")).unwrap();
+        window.add_link(link("b")).unwrap();
+
+        let mut cli = HistoryStorage::in_file(path.clone()).unwrap();
+        cli.remove_link("s.ee", "a").unwrap();
+        window.set_link_title("s.ee", "b", "Page").unwrap();
+
+        assert_eq!(slugs(&window), ["b"]);
+        let reloaded = HistoryStorage::in_file(path).unwrap();
+        assert_eq!(slugs(&reloaded), ["b"]);
+        assert_eq!(reloaded.links()[0].title.as_deref(), Some("Page"));
     }
 }
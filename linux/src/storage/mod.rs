@@ -1,5 +1,11 @@
+mod campaigns;
+mod drafts;
 mod history;
 mod models;
+mod uploads;
 
+pub use campaigns::CampaignPresets;
+pub use drafts::{LinkDraft, TextDraft, TextDrafts};
 pub use history::HistoryStorage;
 pub use models::{FileEntry, LinkEntry, TextEntry};
+pub use uploads::{UploadCheckpoint, UploadCheckpoints, UploadMetadata};
@@ -8,6 +8,9 @@ pub struct LinkEntry {
     pub domain: String,
     pub slug: String,
     pub title: Option<String>,
+    /// `utm_campaign` the destination was tagged with
+    #[serde(default)]
+    pub campaign: Option<String>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -25,6 +28,7 @@ impl LinkEntry {
             domain,
             slug,
             title,
+            campaign: None,
             created_at: Utc::now(),
         }
     }
@@ -40,6 +44,8 @@ pub struct TextEntry {
     pub title: Option<String>,
     pub syntax: Option<String>,
     pub content_preview: String,
+    #[serde(default)]
+    pub encryption_key: Option<String>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -61,9 +67,19 @@ impl TextEntry {
             title,
             syntax,
             content_preview,
+            encryption_key: None,
             created_at: Utc::now(),
         }
     }
+
+    /// Share page URL, with the decryption key in the fragment for encrypted texts
+    pub fn share_url(&self) -> String {
+        let url = self.page_url.as_ref().unwrap_or(&self.url);
+        match self.encryption_key {
+            Some(ref key) => format!("{}#{}", url, key),
+            None => url.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -76,6 +92,19 @@ pub struct FileEntry {
     pub filename: String,
     pub size: u64,
     pub mime_type: Option<String>,
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub height: Option<u32>,
+    #[serde(default)]
+    pub page_count: Option<u32>,
+    /// Hex SHA-256 of the uploaded contents, before any encryption
+    #[serde(default)]
+    pub sha256: Option<String>,
+    #[serde(default)]
+    pub metadata_stripped: bool,
+    #[serde(default)]
+    pub encryption_key: Option<String>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -97,7 +126,22 @@ impl FileEntry {
             filename,
             size,
             mime_type,
+            width: None,
+            height: None,
+            page_count: None,
+            sha256: None,
+            metadata_stripped: false,
+            encryption_key: None,
             created_at: Utc::now(),
         }
     }
+
+    /// Link to share: the share page, or the direct URL with the key in the
+    /// fragment for encrypted files since only the app can decrypt them
+    pub fn share_url(&self) -> String {
+        match self.encryption_key {
+            Some(ref key) => format!("{}#{}", self.url, key),
+            None => self.page_url.as_ref().unwrap_or(&self.url).clone(),
+        }
+    }
 }
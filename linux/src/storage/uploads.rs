@@ -0,0 +1,150 @@
+use crate::config::Config;
+use crate::error::Result;
+use crate::media::{staging, FileInfo};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Details about how a file was prepared before it was sent
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UploadMetadata {
+    /// The uploaded path is a rewritten copy that should be removed afterwards
+    #[serde(default)]
+    pub staged: bool,
+    #[serde(default)]
+    pub metadata_stripped: bool,
+    /// Name recorded in history when the server only sees an encrypted blob
+    #[serde(default)]
+    pub original_filename: Option<String>,
+    #[serde(default)]
+    pub encryption_key: Option<String>,
+    /// Type and dimensions of the file before any encryption
+    #[serde(default)]
+    pub file_info: FileInfo,
+}
+
+/// Progress of a chunked upload, persisted after every chunk so it can resume
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadCheckpoint {
+    pub upload_id: String,
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: Option<u64>,
+    pub chunk_size: u64,
+    pub offset: u64,
+    #[serde(default)]
+    pub metadata: UploadMetadata,
+    pub created_at: DateTime<Utc>,
+}
+
+impl UploadCheckpoint {
+    pub fn new(
+        upload_id: String,
+        path: PathBuf,
+        size: u64,
+        chunk_size: u64,
+        metadata: UploadMetadata,
+    ) -> Self {
+        let modified = modified_secs(&path);
+        Self {
+            upload_id,
+            path,
+            size,
+            modified,
+            chunk_size,
+            offset: 0,
+            metadata,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Whether the local file is still the one this checkpoint was made for
+    pub fn matches_file(&self) -> bool {
+        fs::metadata(&self.path).is_ok_and(|m| m.len() == self.size)
+            && modified_secs(&self.path) == self.modified
+    }
+}
+
+/// Checkpoints of unfinished chunked uploads, one JSON file each
+#[derive(Debug)]
+pub struct UploadCheckpoints {
+    dir: PathBuf,
+}
+
+impl UploadCheckpoints {
+    pub fn load() -> Result<Self> {
+        Ok(Self::in_dir(Config::data_dir()?.join("uploads")))
+    }
+
+    /// Checkpoints kept in a directory of their own
+    pub fn in_dir(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn pending(&self) -> Vec<UploadCheckpoint> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+
+        let mut checkpoints: Vec<UploadCheckpoint> = entries
+            .flatten()
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|e| {
+                let content = fs::read_to_string(e.path()).ok()?;
+                serde_json::from_str(&content).ok()
+            })
+            .collect();
+        checkpoints.sort_by_key(|c| c.created_at);
+        checkpoints
+    }
+
+    pub fn find(&self, path: &Path) -> Option<UploadCheckpoint> {
+        self.pending()
+            .into_iter()
+            .find(|c| c.path == path && c.matches_file())
+    }
+
+    pub fn save(&self, checkpoint: &UploadCheckpoint) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let content = serde_json::to_string_pretty(checkpoint)?;
+        fs::write(self.checkpoint_path(&checkpoint.upload_id), content)?;
+        Ok(())
+    }
+
+    pub fn remove(&self, upload_id: &str) -> Result<()> {
+        let path = self.checkpoint_path(upload_id);
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    /// Drop a checkpoint that can no longer be resumed, along with the staged
+    /// copy it was uploading
+    pub fn abandon(&self, checkpoint: &UploadCheckpoint) -> Result<()> {
+        self.remove(&checkpoint.upload_id)?;
+        if checkpoint.metadata.staged {
+            staging::discard(&checkpoint.path);
+        }
+        Ok(())
+    }
+
+    fn checkpoint_path(&self, upload_id: &str) -> PathBuf {
+        // The id comes from the server, keep it from escaping the directory
+        let name: String = upload_id
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+            .collect();
+        self.dir.join(format!("{}.json", name))
+    }
+}
+
+fn modified_secs(path: &Path) -> Option<u64> {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+}
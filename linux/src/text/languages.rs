@@ -0,0 +1,85 @@
+use crate::api::TextType;
+
+/// A programming or markup language recognised from file names.
+///
+/// Ids follow GtkSourceView's language ids so they can select a highlighter directly.
+#[derive(Debug, PartialEq)]
+pub struct Language {
+    pub id: &'static str,
+    pub name: &'static str,
+    extensions: &'static [&'static str],
+    filenames: &'static [&'static str],
+}
+
+impl Language {
+    const fn new(
+        id: &'static str,
+        name: &'static str,
+        extensions: &'static [&'static str],
+        filenames: &'static [&'static str],
+    ) -> Self {
+        Self {
+            id,
+            name,
+            extensions,
+            filenames,
+        }
+    }
+
+    /// Text type a file in this language is shared as
+    pub fn text_type(&self) -> TextType {
+        if self.id == "markdown" {
+            TextType::Markdown
+        } else {
+            TextType::SourceCode
+        }
+    }
+
+    /// Look up a language by its id
+    pub fn by_id(id: &str) -> Option<&'static Language> {
+        LANGUAGES.iter().find(|l| l.id == id)
+    }
+
+    /// Guess the language of a file from its name or extension
+    pub fn for_filename(filename: &str) -> Option<&'static Language> {
+        let lower = filename.to_lowercase();
+        if let Some(language) = LANGUAGES.iter().find(|l| l.filenames.contains(&lower.as_str())) {
+            return Some(language);
+        }
+
+        let (_, extension) = lower.rsplit_once('.')?;
+        LANGUAGES.iter().find(|l| l.extensions.contains(&extension))
+    }
+}
+
+pub const LANGUAGES: &[Language] = &[
+    Language::new("c", "C", &["c", "h"], &[]),
+    Language::new("c-sharp", "C#", &["cs"], &[]),
+    Language::new("cpp", "C++", &["cpp", "cc", "cxx", "hpp", "hh", "hxx"], &[]),
+    Language::new("css", "CSS", &["css"], &[]),
+    Language::new("diff", "Diff", &["diff", "patch"], &[]),
+    Language::new("dockerfile", "Dockerfile", &[], &["dockerfile", "containerfile"]),
+    Language::new("go", "Go", &["go"], &[]),
+    Language::new("html", "HTML", &["html", "htm"], &[]),
+    Language::new("ini", "INI", &["ini", "cfg", "conf", "desktop"], &[]),
+    Language::new("java", "Java", &["java"], &[]),
+    Language::new("js", "JavaScript", &["js", "mjs", "cjs", "jsx"], &[]),
+    Language::new("json", "JSON", &["json", "geojson"], &[]),
+    Language::new("kotlin", "Kotlin", &["kt", "kts"], &[]),
+    Language::new("lua", "Lua", &["lua"], &[]),
+    Language::new("makefile", "Makefile", &["mk", "mak"], &["makefile", "gnumakefile"]),
+    Language::new("markdown", "Markdown", &["md", "markdown", "mkd"], &[]),
+    Language::new("perl", "Perl", &["pl", "pm"], &[]),
+    Language::new("php", "PHP", &["php"], &[]),
+    Language::new("python3", "Python", &["py", "pyw", "pyi"], &[]),
+    Language::new("ruby", "Ruby", &["rb"], &["gemfile", "rakefile"]),
+    Language::new("rust", "Rust", &["rs"], &[]),
+    Language::new("scss", "SCSS", &["scss"], &[]),
+    Language::new("sh", "Shell", &["sh", "bash", "zsh"], &[".bashrc", ".zshrc", ".profile"]),
+    Language::new("sql", "SQL", &["sql"], &[]),
+    Language::new("swift", "Swift", &["swift"], &[]),
+    Language::new("toml", "TOML", &["toml"], &["cargo.lock"]),
+    Language::new("typescript", "TypeScript", &["ts", "mts", "cts", "tsx"], &[]),
+    Language::new("xml", "XML", &["xml", "xsd", "xsl", "svg", "ui"], &[]),
+    Language::new("yaml", "YAML", &["yaml", "yml"], &[]),
+];
@@ -0,0 +1,87 @@
+use crate::api::TextType;
+use crate::error::{AppError, Result};
+use crate::text::Language;
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_8};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Files larger than this are not loaded into the editor
+pub const MAX_TEXT_FILE_SIZE: u64 = 2 * 1024 * 1024;
+
+/// A local text file decoded for the editor
+#[derive(Debug)]
+pub struct LoadedText {
+    pub content: String,
+    /// File name, used as the default title
+    pub title: String,
+    /// Name of the encoding the file was decoded from, like "UTF-8" or "windows-1252"
+    pub encoding: &'static str,
+    pub text_type: TextType,
+    pub language: Option<&'static Language>,
+}
+
+pub struct TextLoader;
+
+impl TextLoader {
+    /// Read a text file, detecting its encoding and language
+    pub fn load(path: &Path) -> Result<LoadedText> {
+        let title = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let size = path.metadata()?.len();
+        if size > MAX_TEXT_FILE_SIZE {
+            return Err(AppError::UnsupportedFile(format!(
+                "{} is larger than {} MB",
+                title,
+                MAX_TEXT_FILE_SIZE / (1024 * 1024)
+            )));
+        }
+
+        let mut bytes = Vec::with_capacity(size as usize);
+        File::open(path)?
+            .take(MAX_TEXT_FILE_SIZE)
+            .read_to_end(&mut bytes)?;
+
+        let (content, encoding) = Self::decode(&bytes)
+            .ok_or_else(|| AppError::UnsupportedFile(format!("{} is not a text file", title)))?;
+
+        let language = Language::for_filename(&title);
+        let text_type = language.map(Language::text_type).unwrap_or_default();
+
+        Ok(LoadedText {
+            content,
+            title,
+            encoding: encoding.name(),
+            text_type,
+            language,
+        })
+    }
+
+    /// Decode text from a byte order mark, as UTF-8, or from a guessed legacy encoding.
+    /// Returns `None` for binary data.
+    pub fn decode(bytes: &[u8]) -> Option<(String, &'static Encoding)> {
+        if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+            let (content, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+            return Some((content.into_owned(), encoding));
+        }
+
+        // Legacy single and multi-byte encodings never contain NUL in text
+        if bytes.contains(&0) {
+            return None;
+        }
+
+        if let Ok(content) = std::str::from_utf8(bytes) {
+            return Some((content.to_string(), UTF_8));
+        }
+
+        let mut detector = EncodingDetector::new();
+        detector.feed(bytes, true);
+        let encoding = detector.guess(None, true);
+        let (content, _) = encoding.decode_without_bom_handling(bytes);
+        Some((content.into_owned(), encoding))
+    }
+}
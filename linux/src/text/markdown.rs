@@ -0,0 +1,435 @@
+use pulldown_cmark::{Alignment, Event, Options, Parser, Tag, TagEnd};
+
+/// Formatting applied to a run of rendered Markdown text
+#[derive(Debug, Clone, PartialEq)]
+pub enum MarkdownStyle {
+    Heading(u8),
+    Strong,
+    Emphasis,
+    Strikethrough,
+    Code,
+    CodeBlock,
+    Quote,
+    /// Nesting depth of lists and block quotes
+    Indent(u8),
+    Link(String),
+    Rule,
+    TableHeader,
+}
+
+/// A run of text sharing the same formatting
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkdownSpan {
+    pub text: String,
+    pub styles: Vec<MarkdownStyle>,
+}
+
+/// Table cells collected until the table ends and column widths are known
+struct Table {
+    alignments: Vec<Alignment>,
+    rows: Vec<Vec<String>>,
+    header_rows: usize,
+}
+
+/// Renders Markdown into styled text runs for a local preview.
+///
+/// Uses the GitHub flavoured extensions the server renders: tables,
+/// strikethrough and task lists. Nothing is fetched, images show as links.
+pub struct MarkdownRenderer {
+    spans: Vec<MarkdownSpan>,
+    styles: Vec<MarkdownStyle>,
+    /// Next item number of each open list, `None` for bullet lists
+    lists: Vec<Option<u64>>,
+    quote_depth: u8,
+    table: Option<Table>,
+    /// Right after a list bullet, where a paragraph must not start a new line
+    at_item_start: bool,
+}
+
+impl MarkdownRenderer {
+    pub fn render(source: &str) -> Vec<MarkdownSpan> {
+        let options =
+            Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+
+        let mut renderer = Self {
+            spans: Vec::new(),
+            styles: Vec::new(),
+            lists: Vec::new(),
+            quote_depth: 0,
+            table: None,
+            at_item_start: false,
+        };
+        for event in Parser::new_ext(source, options) {
+            renderer.event(event);
+        }
+
+        // Drop the spacing left after the last block
+        while let Some(last) = renderer.spans.last_mut() {
+            let trimmed = last.text.trim_end_matches('\n').len();
+            last.text.truncate(trimmed);
+            if !last.text.is_empty() {
+                break;
+            }
+            renderer.spans.pop();
+        }
+        renderer.spans
+    }
+
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => self.push_text(&text),
+            Event::Code(text) => {
+                self.styles.push(MarkdownStyle::Code);
+                self.push_text(&text);
+                self.styles.pop();
+            }
+            Event::Html(html) | Event::InlineHtml(html) => self.push_text(&html),
+            Event::SoftBreak => self.push_text(" "),
+            Event::HardBreak => self.push_text("\n"),
+            Event::Rule => {
+                self.block_break();
+                self.styles.push(MarkdownStyle::Rule);
+                self.push_text(&"─".repeat(24));
+                self.styles.pop();
+                self.block_break();
+            }
+            Event::TaskListMarker(checked) => {
+                // The checkbox takes the place of the bullet
+                if let Some(last) = self.spans.last_mut() {
+                    if let Some(text) = last.text.strip_suffix("• ") {
+                        last.text = text.to_string();
+                    }
+                }
+                self.push_text(if checked { "☑ " } else { "☐ " });
+                self.at_item_start = true;
+            }
+            _ => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => {
+                if !self.at_item_start {
+                    self.block_break();
+                }
+            }
+            Tag::Heading { level, .. } => {
+                self.block_break();
+                self.styles.push(MarkdownStyle::Heading(level as u8));
+            }
+            Tag::BlockQuote(_) => {
+                self.block_break();
+                self.quote_depth += 1;
+            }
+            Tag::CodeBlock(_) => {
+                self.block_break();
+                self.styles.push(MarkdownStyle::CodeBlock);
+            }
+            Tag::List(start) => {
+                self.block_break();
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.ensure_newlines(1);
+                let bullet = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => "• ".to_string(),
+                };
+                self.push_text(&bullet);
+                self.at_item_start = true;
+            }
+            Tag::Table(alignments) => {
+                self.block_break();
+                self.table = Some(Table {
+                    alignments,
+                    rows: Vec::new(),
+                    header_rows: 0,
+                });
+            }
+            Tag::TableHead | Tag::TableRow => {
+                if let Some(ref mut table) = self.table {
+                    table.rows.push(Vec::new());
+                }
+            }
+            Tag::TableCell => {
+                if let Some(row) = self.table.as_mut().and_then(|t| t.rows.last_mut()) {
+                    row.push(String::new());
+                }
+            }
+            Tag::Emphasis => self.styles.push(MarkdownStyle::Emphasis),
+            Tag::Strong => self.styles.push(MarkdownStyle::Strong),
+            Tag::Strikethrough => self.styles.push(MarkdownStyle::Strikethrough),
+            Tag::Link { dest_url, .. } => {
+                self.styles.push(MarkdownStyle::Link(dest_url.to_string()));
+            }
+            Tag::Image { dest_url, .. } => {
+                self.styles.push(MarkdownStyle::Link(dest_url.to_string()));
+                self.push_text("🖼 ");
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph => self.block_break(),
+            TagEnd::Heading(_) | TagEnd::CodeBlock => {
+                self.styles.pop();
+                self.block_break();
+            }
+            TagEnd::BlockQuote(_) => {
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+                self.block_break();
+            }
+            TagEnd::List(_) => {
+                self.lists.pop();
+                self.block_break();
+            }
+            TagEnd::Item => self.ensure_newlines(1),
+            TagEnd::TableHead => {
+                if let Some(ref mut table) = self.table {
+                    table.header_rows = table.rows.len();
+                }
+            }
+            TagEnd::Table => {
+                if let Some(table) = self.table.take() {
+                    self.push_table(table);
+                }
+                self.block_break();
+            }
+            TagEnd::Emphasis
+            | TagEnd::Strong
+            | TagEnd::Strikethrough
+            | TagEnd::Link
+            | TagEnd::Image => {
+                self.styles.pop();
+            }
+            _ => {}
+        }
+    }
+
+    /// Lay out a table as aligned monospace lines
+    fn push_table(&mut self, table: Table) {
+        let columns = table.rows.iter().map(Vec::len).max().unwrap_or(0);
+        let widths: Vec<usize> = (0..columns)
+            .map(|column| {
+                table
+                    .rows
+                    .iter()
+                    .filter_map(|row| row.get(column))
+                    .map(|cell| cell.chars().count())
+                    .max()
+                    .unwrap_or(0)
+                    .max(3)
+            })
+            .collect();
+
+        self.styles.push(MarkdownStyle::Code);
+        for (index, row) in table.rows.iter().enumerate() {
+            let cells: Vec<String> = widths
+                .iter()
+                .enumerate()
+                .map(|(column, &width)| {
+                    let cell = row.get(column).map(String::as_str).unwrap_or("");
+                    let alignment = table.alignments.get(column).copied();
+                    pad(cell, width, alignment.unwrap_or(Alignment::None))
+                })
+                .collect();
+
+            let header = index < table.header_rows;
+            if header {
+                self.styles.push(MarkdownStyle::TableHeader);
+            }
+            self.push_text(&cells.join(" │ "));
+            if header {
+                self.styles.pop();
+            }
+            self.push_text("\n");
+
+            if index + 1 == table.header_rows {
+                let rule: Vec<String> = widths.iter().map(|&w| "─".repeat(w)).collect();
+                self.push_text(&rule.join("─┼─"));
+                self.push_text("\n");
+            }
+        }
+        self.styles.pop();
+    }
+
+    fn push_text(&mut self, text: &str) {
+        if let Some(cell) = self
+            .table
+            .as_mut()
+            .and_then(|t| t.rows.last_mut())
+            .and_then(|row| row.last_mut())
+        {
+            cell.push_str(text);
+            return;
+        }
+        if text.is_empty() {
+            return;
+        }
+        self.at_item_start = false;
+
+        let mut styles = self.styles.clone();
+        let indent = self.lists.len() as u8 + self.quote_depth;
+        if indent > 0 {
+            styles.push(MarkdownStyle::Indent(indent));
+        }
+        if self.quote_depth > 0 {
+            styles.push(MarkdownStyle::Quote);
+        }
+
+        match self.spans.last_mut() {
+            Some(last) if last.styles == styles => last.text.push_str(text),
+            _ => self.spans.push(MarkdownSpan {
+                text: text.to_string(),
+                styles,
+            }),
+        }
+    }
+
+    /// Separate blocks by a blank line, or a single line break inside lists
+    fn block_break(&mut self) {
+        self.ensure_newlines(if self.lists.is_empty() { 2 } else { 1 });
+    }
+
+    fn ensure_newlines(&mut self, count: usize) {
+        if self.spans.is_empty() {
+            return;
+        }
+
+        let mut trailing = 0;
+        for span in self.spans.iter().rev() {
+            let newlines = span.text.len() - span.text.trim_end_matches('\n').len();
+            trailing += newlines;
+            if newlines < span.text.len() {
+                break;
+            }
+        }
+
+        if trailing < count {
+            let missing = "\n".repeat(count - trailing);
+            match self.spans.last_mut() {
+                Some(last) if last.styles.is_empty() => last.text.push_str(&missing),
+                _ => self.spans.push(MarkdownSpan {
+                    text: missing,
+                    styles: Vec::new(),
+                }),
+            }
+        }
+    }
+}
+
+fn pad(text: &str, width: usize, alignment: Alignment) -> String {
+    let padding = width.saturating_sub(text.chars().count());
+    match alignment {
+        Alignment::Right => format!("{}{}", " ".repeat(padding), text),
+        Alignment::Center => {
+            let left = padding / 2;
+            format!("{}{}{}", " ".repeat(left), text, " ".repeat(padding - left))
+        }
+        Alignment::Left | Alignment::None => format!("{}{}", text, " ".repeat(padding)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use MarkdownStyle::*;
+
+    /// Each span as its text and styles, for comparing whole renderings
+    fn render(source: &str) -> Vec<(String, Vec<MarkdownStyle>)> {
+        MarkdownRenderer::render(source)
+            .into_iter()
+            .map(|span| (span.text, span.styles))
+            .collect()
+    }
+
+    fn span(text: &str, styles: &[MarkdownStyle]) -> (String, Vec<MarkdownStyle>) {
+        (text.to_string(), styles.to_vec())
+    }
+
+    #[test]
+    fn renders_headings_and_nested_emphasis() {
+        assert_eq!(
+            render("# Title\n\nSome *very **bold** text*.\n\n### Small"),
+            [
+                span("Title", &[Heading(1)]),
+                span("\n\nSome ", &[]),
+                span("very ", &[Emphasis]),
+                span("bold", &[Emphasis, Strong]),
+                span(" text", &[Emphasis]),
+                span(".\n\n", &[]),
+                span("Small", &[Heading(3)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn renders_code() {
+        assert_eq!(
+            render("Run `see help`:\n\n```sh\nsee help\nsee upload *.png\n```\n\nDone"),
+            [
+                span("Run ", &[]),
+                span("see help", &[Code]),
+                span(":\n\n", &[]),
+                span("see help\nsee upload *.png\n", &[CodeBlock]),
+                span("\nDone", &[]),
+            ]
+        );
+    }
+
+    #[test]
+    fn lays_out_tables_in_aligned_columns() {
+        assert_eq!(
+            render("| Name | Size |\n|:--|--:|\n| a.png | 12 KB |\n| b | 3 |"),
+            [
+                span("Name  │  Size", &[Code, TableHeader]),
+                span("\n──────┼──────\na.png │ 12 KB\nb     │     3", &[Code]),
+            ]
+        );
+    }
+
+    #[test]
+    fn replaces_bullets_with_task_checkboxes() {
+        assert_eq!(
+            render("- [x] done\n- [ ] todo\n- plain"),
+            [
+                span("☑ done", &[Indent(1)]),
+                span("\n", &[]),
+                span("☐ todo", &[Indent(1)]),
+                span("\n", &[]),
+                span("• plain", &[Indent(1)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn shows_images_as_links() {
+        assert_eq!(
+            render("![A cat](https://example.com/cat.png) and [home](https://s.ee)"),
+            [
+                span(
+                    "🖼 A cat",
+                    &[Link("https://example.com/cat.png".to_string())]
+                ),
+                span(" and ", &[]),
+                span("home", &[Link("https://s.ee".to_string())]),
+            ]
+        );
+    }
+
+    #[test]
+    fn shows_html_as_text() {
+        assert_eq!(
+            render("<script>alert(1)</script>\n\nHi <b>there</b>"),
+            [span("<script>alert(1)</script>\n\nHi <b>there</b>", &[])]
+        );
+    }
+}
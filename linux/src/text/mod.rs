@@ -0,0 +1,7 @@
+mod languages;
+mod loader;
+mod markdown;
+
+pub use languages::{Language, LANGUAGES};
+pub use loader::TextLoader;
+pub use markdown::{MarkdownRenderer, MarkdownStyle};
@@ -1,14 +1,21 @@
 use crate::api::async_bridge::{spawn_api_call, ApiRequest, ApiResponse};
 use crate::config::Config;
-use crate::storage::{FileEntry, HistoryStorage};
-use crate::views::QrDialog;
-use crate::widgets::FileRow;
+use crate::crypto::{ContentCipher, EncryptionKey, MAX_ENCRYPTED_FILE_SIZE};
+use crate::error::{AppError, Result};
+use crate::format::{LinkFormat, LinkFormatter};
+use crate::media::{
+    staging, FileInspector, ImageOptions, ImageProcessor, ThumbnailCache, UploadLimits,
+    MAX_THUMBNAIL_SOURCE_SIZE,
+};
+use crate::storage::{FileEntry, HistoryStorage, UploadCheckpoints, UploadMetadata};
+use crate::views::{QrDialog, UploadReviewDialog};
+use crate::widgets::{FileRow, FileTile};
 use adw::prelude::*;
 use adw::subclass::prelude::*;
 use gtk::{gdk, gio, glib};
 use std::cell::{Cell, RefCell};
-use std::io::Write;
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 
 const ITEMS_PER_PAGE: usize = 10;
 
@@ -18,8 +25,12 @@ mod imp {
     #[derive(Debug, Default)]
     pub struct FilesView {
         pub domain_combo: RefCell<Option<adw::ComboRow>>,
+        pub encrypt_switch: RefCell<Option<adw::SwitchRow>>,
         pub upload_button: RefCell<Option<gtk::Button>>,
         pub files_list: RefCell<Option<gtk::ListBox>>,
+        pub files_grid: RefCell<Option<gtk::FlowBox>>,
+        /// File URLs whose thumbnail is being fetched or could not be made
+        pub thumbnail_requests: RefCell<HashSet<String>>,
         pub toast_overlay: RefCell<Option<adw::ToastOverlay>>,
         pub storage: RefCell<Option<HistoryStorage>>,
         pub drop_target: RefCell<Option<gtk::DropTarget>>,
@@ -112,7 +123,13 @@ impl FilesView {
             .subtitle("Loading domains...")
             .build();
 
+        let encrypt_switch = adw::SwitchRow::builder()
+            .title("Encrypt")
+            .subtitle("Encrypt on this device; only people with the full link can open the file")
+            .build();
+
         upload_group.add(&domain_combo);
+        upload_group.add(&encrypt_switch);
 
         // Drop target area with improved styling
         let drop_area = gtk::Box::builder()
@@ -135,7 +152,7 @@ impl FilesView {
         drop_label.add_css_class("drop-zone-text");
 
         let drop_hint = gtk::Label::builder()
-            .label("Maximum file size: 100 MB")
+            .label(UploadLimits::from_config(&Config::load().unwrap_or_default()).describe())
             .build();
         drop_hint.add_css_class("drop-zone-hint");
 
@@ -165,7 +182,28 @@ impl FilesView {
             .css_classes(["flat", "clear-history"])
             .build();
         clear_button.set_tooltip_text(Some("Clear local history"));
-        recent_group.set_header_suffix(Some(&clear_button));
+
+        let open_encrypted_button = gtk::Button::builder()
+            .label("Open Encrypted…")
+            .css_classes(["flat"])
+            .build();
+        open_encrypted_button.set_tooltip_text(Some("Download and decrypt a file from a key link"));
+
+        let gallery_button = gtk::ToggleButton::builder()
+            .icon_name("view-grid-symbolic")
+            .css_classes(["flat"])
+            .active(Config::load().unwrap_or_default().file_gallery())
+            .build();
+        gallery_button.set_tooltip_text(Some("Show as gallery"));
+
+        let header_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(4)
+            .build();
+        header_box.append(&gallery_button);
+        header_box.append(&open_encrypted_button);
+        header_box.append(&clear_button);
+        recent_group.set_header_suffix(Some(&header_box));
 
         let files_list = gtk::ListBox::builder()
             .selection_mode(gtk::SelectionMode::None)
@@ -208,6 +246,19 @@ impl FilesView {
 
         recent_group.add(&files_list);
 
+        // Gallery layout, used instead of the list when enabled
+        let files_grid = gtk::FlowBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .activate_on_single_click(true)
+            .homogeneous(true)
+            .min_children_per_line(2)
+            .max_children_per_line(4)
+            .row_spacing(8)
+            .column_spacing(8)
+            .visible(false)
+            .build();
+        recent_group.add(&files_grid);
+
         // Pagination controls
         let pagination_box = gtk::Box::builder()
             .orientation(gtk::Orientation::Horizontal)
@@ -291,8 +342,10 @@ impl FilesView {
 
         // Store references
         imp.domain_combo.replace(Some(domain_combo.clone()));
+        imp.encrypt_switch.replace(Some(encrypt_switch.clone()));
         imp.upload_button.replace(Some(upload_button.clone()));
         imp.files_list.replace(Some(files_list.clone()));
+        imp.files_grid.replace(Some(files_grid.clone()));
         imp.toast_overlay.replace(Some(toast_overlay.clone()));
         imp.drop_target.replace(Some(drop_target));
         imp.page_label.replace(Some(page_label.clone()));
@@ -317,6 +370,40 @@ impl FilesView {
             }
         ));
 
+        // Switch between list and gallery
+        gallery_button.connect_toggled(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |button| {
+                let mut config = Config::load().unwrap_or_default();
+                config.file_gallery = Some(button.is_active());
+                if let Err(e) = config.save() {
+                    log::error!("Failed to save config: {}", e);
+                }
+                view.refresh_list();
+            }
+        ));
+
+        // Open a file from the gallery like a list row
+        files_grid.connect_child_activated(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_, child| {
+                if let Some(entry) = child.downcast_ref::<FileTile>().and_then(|t| t.entry()) {
+                    view.open_entry(&entry);
+                }
+            }
+        ));
+
+        // Connect open encrypted button
+        open_encrypted_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.show_open_encrypted_dialog();
+            }
+        ));
+
         // Connect pagination buttons
         prev_button.connect_clicked(glib::clone!(
             #[weak(rename_to = view)]
@@ -345,6 +432,43 @@ impl FilesView {
 
         // Fetch domains from API
         self.fetch_domains();
+
+        // Continue chunked uploads left unfinished by the last session
+        self.resume_pending_uploads();
+    }
+
+    fn resume_pending_uploads(&self) {
+        let Ok(checkpoints) = UploadCheckpoints::load() else {
+            return;
+        };
+
+        let pending = checkpoints.pending();
+        let in_use: Vec<PathBuf> = pending.iter().map(|c| c.path.clone()).collect();
+        staging::remove_stale(&in_use);
+
+        for checkpoint in pending {
+            if !checkpoint.matches_file() {
+                // The file is gone or changed since, the upload can't be completed
+                if let Err(e) = checkpoints.abandon(&checkpoint) {
+                    log::error!("Failed to remove upload checkpoint: {}", e);
+                }
+                continue;
+            }
+
+            let filename = checkpoint
+                .metadata
+                .original_filename
+                .clone()
+                .or_else(|| {
+                    checkpoint
+                        .path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().into_owned())
+                })
+                .unwrap_or_default();
+            self.show_toast(&format!("Resuming upload of {}", filename));
+            self.start_upload(checkpoint.path, checkpoint.metadata);
+        }
     }
 
     fn show_clear_history_dialog(&self) {
@@ -380,8 +504,7 @@ impl FilesView {
     fn clear_local_history(&self) {
         let imp = self.imp();
         if let Some(ref mut storage) = *imp.storage.borrow_mut() {
-            storage.clear_files();
-            if let Err(e) = storage.save() {
+            if let Err(e) = storage.clear_files() {
                 log::error!("Failed to save after clearing history: {}", e);
             }
         }
@@ -459,20 +582,313 @@ impl FilesView {
         );
     }
 
-    fn upload_file(&self, path: PathBuf) {
+    /// Upload a file from disk, with the same review and processing as a dropped file
+    pub fn upload_file(&self, path: PathBuf) {
         let config = Config::load().unwrap_or_default();
+        let strip_metadata = config.strip_image_metadata();
+        let image_options = config
+            .process_dropped_images()
+            .then(|| ImageOptions::from_config(&config));
 
-        let request = ApiRequest::UploadFile { path: path.clone() };
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            async move {
+                let mut info = UploadMetadata::default();
+                let mut upload_path = path.clone();
+
+                if let Some(options) = image_options {
+                    let source = path.clone();
+                    let result = gio::spawn_blocking(move || {
+                        ImageProcessor::process_file(&source, &options)
+                    })
+                    .await;
+                    match result {
+                        Ok(Ok(Some(processed))) => {
+                            // Re-encoding never carries the original metadata over
+                            upload_path = processed;
+                            info.staged = true;
+                            info.metadata_stripped = true;
+                        }
+                        Ok(Ok(None)) => {}
+                        Ok(Err(e)) => {
+                            view.show_toast(&format!("Failed to process image: {}", e));
+                            return;
+                        }
+                        Err(_) => {
+                            view.show_toast("Failed to process image");
+                            return;
+                        }
+                    }
+                }
 
-        let receiver = spawn_api_call(config, request);
+                if strip_metadata && !info.metadata_stripped {
+                    let source = path.clone();
+                    let result =
+                        gio::spawn_blocking(move || ImageProcessor::strip_metadata(&source)).await;
+                    match result {
+                        Ok(Ok(Some(stripped))) => {
+                            upload_path = stripped;
+                            info.staged = true;
+                            info.metadata_stripped = true;
+                        }
+                        Ok(Ok(None)) => {}
+                        Ok(Err(e)) => {
+                            // Never fall back to the original, it may carry location data
+                            view.show_toast(&format!("Failed to remove image metadata: {}", e));
+                            return;
+                        }
+                        Err(_) => {
+                            view.show_toast("Failed to remove image metadata");
+                            return;
+                        }
+                    }
+                }
+
+                view.send_upload(upload_path, info);
+            }
+        ));
+    }
+
+    fn send_upload(&self, path: PathBuf, info: UploadMetadata) {
+        let config = Config::load().unwrap_or_default();
+        let limits = UploadLimits::from_config(&config);
+        let review = config.review_uploads();
+        let copy_format = config.default_copy_format();
+        let encrypt = self
+            .imp()
+            .encrypt_switch
+            .borrow()
+            .as_ref()
+            .is_some_and(|s| s.is_active());
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            async move {
+                let mut path = path;
+                let mut info = info;
+
+                let source = path.clone();
+                match gio::spawn_blocking(move || FileInspector::inspect(&source)).await {
+                    Ok(Ok(file_info)) => info.file_info = file_info,
+                    Ok(Err(e)) => log::warn!("Failed to inspect file: {}", e),
+                    Err(_) => log::warn!("Failed to inspect file"),
+                }
+
+                if let Err(e) = view.check_limits(&path, &info, &limits) {
+                    view.show_toast(&e.to_string());
+                    if info.staged {
+                        staging::discard(&path);
+                    }
+                    return;
+                }
+
+                // Offer the earlier link when the same contents were uploaded before
+                let duplicate = info.file_info.sha256.as_deref().and_then(|digest| {
+                    view.imp()
+                        .storage
+                        .borrow()
+                        .as_ref()
+                        .and_then(|s| s.find_file_by_hash(digest, encrypt).cloned())
+                });
+                if let Some(existing) = duplicate {
+                    let response = view.ask_reuse_duplicate(&existing).await;
+                    if response != "upload" {
+                        if info.staged {
+                            staging::discard(&path);
+                        }
+                        if response == "reuse" {
+                            let url = if existing.encryption_key.is_some() {
+                                existing.share_url()
+                            } else {
+                                LinkFormatter::format(&existing, copy_format)
+                            };
+                            if let Some(display) = gdk::Display::default() {
+                                display.clipboard().set_text(&url);
+                            }
+                            view.show_toast("Existing link copied");
+                        }
+                        return;
+                    }
+                }
+
+                if review {
+                    let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+                    let dialog = UploadReviewDialog::new(&path, size, &info.file_info);
+                    if dialog.clone().choose_future(&view).await != "upload" {
+                        if info.staged {
+                            staging::discard(&path);
+                        }
+                        return;
+                    }
+
+                    let filename = dialog.filename();
+                    if path.file_name().is_some_and(|n| *n != *filename) {
+                        let source = path.clone();
+                        let name = filename.clone();
+                        let result =
+                            gio::spawn_blocking(move || staging::stage_as(&source, &name)).await;
+
+                        if info.staged {
+                            staging::discard(&path);
+                        }
+
+                        match result {
+                            Ok(Ok(renamed)) => {
+                                path = renamed;
+                                info.staged = true;
+                            }
+                            Ok(Err(e)) => {
+                                view.show_toast(&format!("Failed to rename file: {}", e));
+                                return;
+                            }
+                            Err(_) => {
+                                view.show_toast("Failed to rename file");
+                                return;
+                            }
+                        }
+
+                        // Type rules may depend on the extension that was just changed
+                        if let Err(e) = view.check_limits(&path, &info, &limits) {
+                            view.show_toast(&e.to_string());
+                            staging::discard(&path);
+                            return;
+                        }
+                    }
+                }
+
+                if encrypt {
+                    let filename = path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or("file")
+                        .to_string();
+                    let key = EncryptionKey::generate();
+                    let fragment = key.to_fragment();
+
+                    let source = path.clone();
+                    let name = filename.clone();
+                    let result =
+                        gio::spawn_blocking(move || encrypt_to_staging(&source, &name, &key)).await;
+
+                    if info.staged {
+                        staging::discard(&path);
+                    }
+
+                    match result {
+                        Ok(Ok(encrypted)) => {
+                            path = encrypted;
+                            info.staged = true;
+                            info.original_filename = Some(filename);
+                            info.encryption_key = Some(fragment);
+                        }
+                        Ok(Err(e)) => {
+                            view.show_toast(&format!("Failed to encrypt file: {}", e));
+                            return;
+                        }
+                        Err(_) => {
+                            view.show_toast("Failed to encrypt file");
+                            return;
+                        }
+                    }
+                }
+
+                view.start_upload(path, info);
+            }
+        ));
+    }
+
+    /// Ask whether to reuse an earlier upload; responds "reuse", "upload" or "cancel"
+    async fn ask_reuse_duplicate(&self, existing: &FileEntry) -> glib::GString {
+        let uploaded = existing
+            .created_at
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M");
+        let dialog = adw::AlertDialog::builder()
+            .heading("Already Uploaded")
+            .body(format!(
+                "The same file was uploaded as \"{}\" on {}. Reuse its link or upload it again?",
+                existing.filename, uploaded
+            ))
+            .build();
+
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("upload", "Upload Again");
+        dialog.add_response("reuse", "Copy Existing Link");
+        dialog.set_response_appearance("reuse", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("reuse"));
+        dialog.set_close_response("cancel");
+
+        dialog.choose_future(self).await
+    }
 
-        self.show_toast("Uploading file...");
+    fn check_limits(
+        &self,
+        path: &Path,
+        info: &UploadMetadata,
+        limits: &UploadLimits,
+    ) -> Result<()> {
+        let filename = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let size = std::fs::metadata(path)?.len();
+        limits.check(&filename, size, info.file_info.mime_type.as_deref())
+    }
 
+    /// Send a prepared file and record it in history
+    fn start_upload(&self, path: PathBuf, info: UploadMetadata) {
         glib::spawn_future_local(glib::clone!(
             #[weak(rename_to = view)]
             self,
             async move {
+                let config = Config::load().unwrap_or_default();
+                let copy_format = config.default_copy_format();
+
+                let request = ApiRequest::UploadFile {
+                    path: path.clone(),
+                    metadata: info.clone(),
+                };
+
+                let receiver = spawn_api_call(config, request);
+
+                if info.encryption_key.is_some() {
+                    view.show_toast("Uploading encrypted file...");
+                } else if info.metadata_stripped {
+                    view.show_toast("Uploading file (metadata removed)...");
+                } else {
+                    view.show_toast("Uploading file...");
+                }
+
                 if let Ok(response) = receiver.recv().await {
+                    // Make the thumbnail from the local file before a staged copy is removed
+                    if let ApiResponse::UploadFile(Ok(ref result)) = response {
+                        let mime_type = info.file_info.mime_type.as_deref();
+                        if info.encryption_key.is_none()
+                            && ThumbnailCache::supports(mime_type, &result.data.filename)
+                        {
+                            let url = result.data.url.clone();
+                            let source = path.clone();
+                            let generated = gio::spawn_blocking(move || {
+                                ThumbnailCache::load()?.generate_from_file(&url, &source)
+                            })
+                            .await;
+                            if let Ok(Err(e)) = generated {
+                                log::warn!("Failed to create thumbnail: {}", e);
+                            }
+                        }
+                    }
+
+                    // Keep the staged copy around while a chunked upload can still resume
+                    let resumable = UploadCheckpoints::load()
+                        .ok()
+                        .and_then(|c| c.find(&path))
+                        .is_some();
+                    if info.staged && !resumable {
+                        staging::discard(&path);
+                    }
+
                     match response {
                         ApiResponse::UploadFile(Ok(result)) => {
                             // Extract domain from url
@@ -486,15 +902,27 @@ impl FilesView {
                             // Get page URL from API response
                             let page_url = result.data.page.clone();
 
-                            let entry = FileEntry::new(
+                            let mut entry = FileEntry::new(
                                 file_url.clone(),
                                 page_url,
                                 domain,
                                 result.data.hash.clone(),
-                                result.data.filename.clone(),
+                                info.original_filename
+                                    .unwrap_or_else(|| result.data.filename.clone()),
                                 result.data.size,
-                                None,
+                                info.file_info.mime_type,
                             );
+                            entry.width = info.file_info.width;
+                            entry.height = info.file_info.height;
+                            entry.page_count = info.file_info.page_count;
+                            entry.sha256 = info.file_info.sha256;
+                            entry.metadata_stripped = info.metadata_stripped;
+                            entry.encryption_key = info.encryption_key;
+                            let copy_url = if entry.encryption_key.is_some() {
+                                entry.share_url()
+                            } else {
+                                LinkFormatter::format(&entry, copy_format)
+                            };
 
                             // Save to storage
                             if let Some(ref mut storage) = *view.imp().storage.borrow_mut() {
@@ -510,9 +938,13 @@ impl FilesView {
 
                             // Copy to clipboard
                             if let Some(display) = gdk::Display::default() {
-                                display.clipboard().set_text(file_url);
+                                display.clipboard().set_text(&copy_url);
                             }
                         }
+                        ApiResponse::UploadFile(Err(e)) if resumable => {
+                            log::warn!("Upload interrupted: {}", e);
+                            view.show_toast("Upload interrupted, it will resume on next launch");
+                        }
                         ApiResponse::UploadFile(Err(e)) => {
                             view.show_toast(&format!("Error: {}", e));
                         }
@@ -531,10 +963,18 @@ impl FilesView {
             None => return,
         };
 
+        let files_grid = match imp.files_grid.borrow().as_ref() {
+            Some(grid) => grid.clone(),
+            None => return,
+        };
+
         // Clear existing rows
         while let Some(child) = files_list.first_child() {
             files_list.remove(&child);
         }
+        files_grid.remove_all();
+
+        let mut entries = Vec::new();
 
         // Get files from storage with pagination
         if let Some(ref storage) = *imp.storage.borrow() {
@@ -555,16 +995,32 @@ impl FilesView {
             let start = current_page * ITEMS_PER_PAGE;
             let end = (start + ITEMS_PER_PAGE).min(total_items);
 
-            // Add rows for current page
+            // The gallery has no placeholder, keep the list for the empty state
+            let gallery = Config::load().unwrap_or_default().file_gallery() && total_items > 0;
+            files_list.set_visible(!gallery);
+            files_grid.set_visible(gallery);
+
+            // Add rows or tiles for current page
             for entry in all_files.iter().skip(start).take(end - start) {
-                let row = FileRow::new(entry);
-                self.setup_row_actions(&row);
-                files_list.append(&row);
+                if gallery {
+                    let icon_name = FileRow::icon_name(entry.mime_type.as_deref());
+                    files_grid.append(&FileTile::new(entry, icon_name));
+                } else {
+                    let row = FileRow::new(entry);
+                    self.setup_row_actions(&row);
+                    files_list.append(&row);
+                }
+                entries.push(entry.clone());
             }
 
             // Update pagination controls
             self.update_pagination(current_page, total_pages);
         }
+
+        // Storage is no longer borrowed, thumbnails may be applied right away
+        for entry in &entries {
+            self.load_thumbnail(entry);
+        }
     }
 
     fn update_pagination(&self, current_page: usize, total_pages: usize) {
@@ -587,17 +1043,134 @@ impl FilesView {
         }
     }
 
+    /// Open the share page in the browser, encrypted files are decrypted in the app
+    fn open_entry(&self, entry: &FileEntry) {
+        if entry.encryption_key.is_some() {
+            self.download_encrypted(&entry.share_url());
+        } else {
+            let url = entry.page_url.as_ref().unwrap_or(&entry.url);
+            let _ = gio::AppInfo::launch_default_for_uri(url, None::<&gio::AppLaunchContext>);
+        }
+    }
+
+    /// Show a cached thumbnail, or fetch the image to make one for older entries
+    fn load_thumbnail(&self, entry: &FileEntry) {
+        // Encrypted uploads are opaque blobs, and a local copy would defeat the point
+        if entry.encryption_key.is_some()
+            || !ThumbnailCache::supports(entry.mime_type.as_deref(), &entry.filename)
+        {
+            return;
+        }
+
+        let Ok(cache) = ThumbnailCache::load() else {
+            return;
+        };
+        if let Some(path) = cache.get(&entry.url) {
+            self.apply_thumbnail(&entry.url, &path);
+            return;
+        }
+
+        if entry.size > MAX_THUMBNAIL_SOURCE_SIZE
+            || !self
+                .imp()
+                .thumbnail_requests
+                .borrow_mut()
+                .insert(entry.url.clone())
+        {
+            return;
+        }
+
+        let url = entry.url.clone();
+        let config = Config::load().unwrap_or_default();
+        let receiver = spawn_api_call(config, ApiRequest::FetchFile { url: url.clone() });
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            async move {
+                let Ok(ApiResponse::FetchFile(result)) = receiver.recv().await else {
+                    return;
+                };
+                let bytes = match result {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        log::warn!("Failed to fetch image for thumbnail: {}", e);
+                        return;
+                    }
+                };
+
+                let key = url.clone();
+                let result =
+                    gio::spawn_blocking(move || cache.generate_from_bytes(&key, &bytes)).await;
+                match result {
+                    Ok(Ok(path)) => {
+                        view.imp().thumbnail_requests.borrow_mut().remove(&url);
+                        view.apply_thumbnail(&url, &path);
+                    }
+                    Ok(Err(e)) => log::warn!("Failed to create thumbnail: {}", e),
+                    Err(_) => log::warn!("Failed to create thumbnail"),
+                }
+            }
+        ));
+    }
+
+    /// Set a thumbnail on every row or tile currently showing the file
+    fn apply_thumbnail(&self, url: &str, path: &Path) {
+        let imp = self.imp();
+
+        if let Some(list) = imp.files_list.borrow().as_ref() {
+            let mut child = list.first_child();
+            while let Some(widget) = child {
+                if let Some(row) = widget.downcast_ref::<FileRow>() {
+                    if row.entry().is_some_and(|e| e.url == url) {
+                        row.set_thumbnail(path);
+                    }
+                }
+                child = widget.next_sibling();
+            }
+        }
+
+        if let Some(grid) = imp.files_grid.borrow().as_ref() {
+            let mut child = grid.first_child();
+            while let Some(widget) = child {
+                if let Some(tile) = widget.downcast_ref::<FileTile>() {
+                    if tile.entry().is_some_and(|e| e.url == url) {
+                        tile.set_thumbnail(path);
+                    }
+                }
+                child = widget.next_sibling();
+            }
+        }
+    }
+
     fn setup_row_actions(&self, row: &FileRow) {
         let entry = row.entry();
 
-        // Click on row to open share page in browser
-        let entry_for_activate = entry.clone();
-        row.connect_activated(move |_| {
-            if let Some(ref entry) = entry_for_activate {
-                let url = entry.page_url.as_ref().unwrap_or(&entry.url);
-                let _ = gio::AppInfo::launch_default_for_uri(url, None::<&gio::AppLaunchContext>);
+        // Click on row to open share page in browser, encrypted files are decrypted in the app
+        row.connect_activated(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            #[strong]
+            entry,
+            move |_| {
+                if let Some(ref entry) = entry {
+                    view.open_entry(entry);
+                }
+            }
+        ));
+
+        // Download & decrypt button
+        row.download_button().connect_clicked(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            #[strong]
+            entry,
+            move |_| {
+                if let Some(ref entry) = entry {
+                    view.download_encrypted(&entry.share_url());
+                }
             }
-        });
+        ));
 
         // Copy Page URL button
         row.copy_page_button().connect_clicked(glib::clone!(
@@ -608,8 +1181,7 @@ impl FilesView {
             move |_| {
                 if let Some(ref entry) = entry {
                     if let Some(display) = gdk::Display::default() {
-                        let url = entry.page_url.as_ref().unwrap_or(&entry.url);
-                        display.clipboard().set_text(url);
+                        display.clipboard().set_text(&entry.share_url());
                         view.show_toast("Share page URL copied");
                     }
                 }
@@ -625,13 +1197,40 @@ impl FilesView {
             move |_| {
                 if let Some(ref entry) = entry {
                     if let Some(display) = gdk::Display::default() {
-                        display.clipboard().set_text(&entry.url);
+                        // Encrypted files are useless without the key fragment
+                        let url = if entry.encryption_key.is_some() {
+                            entry.share_url()
+                        } else {
+                            entry.url.clone()
+                        };
+                        display.clipboard().set_text(&url);
                         view.show_toast("Direct URL copied");
                     }
                 }
             }
         ));
 
+        // Copy as… menu
+        if let Some(action) = row.copy_as_action() {
+            action.connect_activate(glib::clone!(
+                #[weak(rename_to = view)]
+                self,
+                #[strong]
+                entry,
+                move |_, parameter| {
+                    let format = parameter.and_then(|p| p.str()).and_then(LinkFormat::parse);
+                    if let (Some(entry), Some(format)) = (entry.as_ref(), format) {
+                        if let Some(display) = gdk::Display::default() {
+                            display
+                                .clipboard()
+                                .set_text(&LinkFormatter::format(entry, format));
+                            view.show_toast(&format!("Copied as {}", format.label()));
+                        }
+                    }
+                }
+            ));
+        }
+
         // QR button - use page URL
         row.qr_button().connect_clicked(glib::clone!(
             #[weak(rename_to = view)]
@@ -640,8 +1239,7 @@ impl FilesView {
             entry,
             move |_| {
                 if let Some(ref entry) = entry {
-                    let url = entry.page_url.as_ref().unwrap_or(&entry.url);
-                    let dialog = QrDialog::new(url);
+                    let dialog = QrDialog::new(&entry.share_url());
                     if let Some(window) = view.root().and_downcast_ref::<gtk::Window>() {
                         dialog.present(Some(window));
                     }
@@ -703,6 +1301,131 @@ impl FilesView {
         ));
     }
 
+    fn show_open_encrypted_dialog(&self) {
+        let url_entry = gtk::Entry::builder()
+            .placeholder_text("https://i.s.ee/abc.bin#key")
+            .activates_default(true)
+            .build();
+
+        let dialog = adw::AlertDialog::builder()
+            .heading("Open Encrypted File")
+            .body("Paste the full link, including the key after the #")
+            .extra_child(&url_entry)
+            .build();
+
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("open", "Download & Decrypt");
+        dialog.set_response_appearance("open", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("open"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = view)]
+                self,
+                #[weak]
+                url_entry,
+                move |_, response| {
+                    if response == "open" {
+                        view.download_encrypted(url_entry.text().trim());
+                    }
+                }
+            ),
+        );
+
+        if let Some(window) = self.root().and_downcast_ref::<gtk::Window>() {
+            dialog.present(Some(window));
+        }
+    }
+
+    /// Download an encrypted file, decrypt it and save it under its original name
+    fn download_encrypted(&self, url: &str) {
+        let Some((file_url, fragment)) = url.split_once('#') else {
+            self.show_toast("The link has no decryption key");
+            return;
+        };
+
+        let key = match EncryptionKey::from_fragment(fragment) {
+            Ok(key) => key,
+            Err(e) => {
+                self.show_toast(&format!("Error: {}", e));
+                return;
+            }
+        };
+
+        let config = Config::load().unwrap_or_default();
+        let request = ApiRequest::FetchFile {
+            url: file_url.to_string(),
+        };
+
+        let receiver = spawn_api_call(config, request);
+        self.show_toast("Downloading file...");
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            async move {
+                let data = match receiver.recv().await {
+                    Ok(ApiResponse::FetchFile(Ok(data))) => data,
+                    Ok(ApiResponse::FetchFile(Err(e))) => {
+                        view.show_toast(&format!("Error: {}", e));
+                        return;
+                    }
+                    _ => return,
+                };
+
+                let result =
+                    gio::spawn_blocking(move || ContentCipher::decrypt_file(&key, &data)).await;
+                match result {
+                    Ok(Ok((filename, contents))) => {
+                        view.save_decrypted(&filename, contents);
+                    }
+                    Ok(Err(e)) => {
+                        view.show_toast(&format!("Error: {}", e));
+                    }
+                    Err(_) => {
+                        view.show_toast("Failed to decrypt file");
+                    }
+                }
+            }
+        ));
+    }
+
+    fn save_decrypted(&self, filename: &str, contents: Vec<u8>) {
+        // Never trust a path coming out of the encrypted payload
+        let filename = Path::new(filename)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("decrypted");
+
+        let dialog = gtk::FileDialog::builder()
+            .title("Save Decrypted File")
+            .initial_name(filename)
+            .build();
+
+        dialog.save(
+            self.root().and_downcast_ref::<gtk::Window>(),
+            None::<&gio::Cancellable>,
+            glib::clone!(
+                #[weak(rename_to = view)]
+                self,
+                move |result| {
+                    if let Ok(file) = result {
+                        if let Some(path) = file.path() {
+                            match std::fs::write(&path, &contents) {
+                                Ok(()) => view.show_toast("Decrypted file saved"),
+                                Err(e) => {
+                                    view.show_toast(&format!("Failed to save file: {}", e));
+                                }
+                            }
+                        }
+                    }
+                }
+            ),
+        );
+    }
+
     fn show_toast(&self, message: &str) {
         if let Some(ref overlay) = *self.imp().toast_overlay.borrow() {
             let toast = adw::Toast::new(message);
@@ -739,7 +1462,8 @@ impl FilesView {
         }
     }
 
-    fn try_paste_image(&self) {
+    /// Upload an image from the clipboard
+    pub fn try_paste_image(&self) {
         if let Some(display) = gdk::Display::default() {
             let clipboard = display.clipboard();
 
@@ -762,27 +1486,56 @@ impl FilesView {
     }
 
     fn upload_texture(&self, texture: gdk::Texture) {
-        // Save texture to temporary file
         let bytes = texture.save_to_png_bytes();
+        let options = ImageOptions::from_config(&Config::load().unwrap_or_default());
 
-        // Create temp file
-        let temp_dir = std::env::temp_dir();
         let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
-        let temp_path = temp_dir.join(format!("clipboard_{}.png", timestamp));
+        let stem = format!("clipboard_{}", timestamp);
 
-        match std::fs::File::create(&temp_path) {
-            Ok(mut file) => {
-                if let Err(e) = file.write_all(&bytes) {
-                    self.show_toast(&format!("Failed to save image: {}", e));
-                    return;
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            async move {
+                // Encode in the configured format off the main thread
+                let result = gio::spawn_blocking(move || {
+                    ImageProcessor::process_bytes(&bytes, &stem, &options)
+                })
+                .await;
+
+                match result {
+                    Ok(Ok(path)) => {
+                        let info = UploadMetadata {
+                            staged: true,
+                            ..Default::default()
+                        };
+                        view.send_upload(path, info);
+                    }
+                    Ok(Err(e)) => {
+                        view.show_toast(&format!("Failed to save image: {}", e));
+                    }
+                    Err(_) => {
+                        view.show_toast("Failed to save image");
+                    }
                 }
-                self.upload_file(temp_path);
-            }
-            Err(e) => {
-                self.show_toast(&format!("Failed to create temp file: {}", e));
             }
-        }
+        ));
+    }
+}
+
+/// Encrypt a file into a staged blob that hides its name and contents from the server
+fn encrypt_to_staging(path: &Path, filename: &str, key: &EncryptionKey) -> Result<PathBuf> {
+    // Refuse before reading, since encrypting needs the whole file in memory
+    if std::fs::metadata(path)?.len() > MAX_ENCRYPTED_FILE_SIZE {
+        return Err(AppError::Crypto(format!(
+            "files over {} MB can't be encrypted",
+            MAX_ENCRYPTED_FILE_SIZE / (1024 * 1024)
+        )));
     }
+    let contents = std::fs::read(path)?;
+    let blob = ContentCipher::encrypt_file(key, filename, &contents)?;
+    let staged = staging::staging_path("encrypted.bin")?;
+    std::fs::write(&staged, blob)?;
+    Ok(staged)
 }
 
 impl Default for FilesView {
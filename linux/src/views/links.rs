@@ -1,12 +1,19 @@
 use crate::api::async_bridge::{spawn_api_call, ApiRequest, ApiResponse};
 use crate::config::Config;
-use crate::storage::{HistoryStorage, LinkEntry};
-use crate::views::QrDialog;
+use crate::error::Result;
+use crate::links::{
+    Campaign, DuplicateLinkPolicy, FaviconCache, PageFetcher, RedirectTracer, UrlNormalizer,
+    UrlRewriter,
+};
+use crate::storage::{CampaignPresets, HistoryStorage, LinkDraft, LinkEntry};
+use crate::views::{ask_name, QrDialog, RedirectDialog};
 use crate::widgets::LinkRow;
 use adw::prelude::*;
 use adw::subclass::prelude::*;
 use gtk::{gdk, gio, glib};
 use std::cell::{Cell, RefCell};
+use std::time::Duration;
+use url::Url;
 
 const ITEMS_PER_PAGE: usize = 10;
 
@@ -18,11 +25,41 @@ mod imp {
         pub url_entry: RefCell<Option<adw::EntryRow>>,
         pub domain_combo: RefCell<Option<adw::ComboRow>>,
         pub slug_entry: RefCell<Option<adw::EntryRow>>,
+        pub title_entry: RefCell<Option<adw::EntryRow>>,
+        pub url_error_label: RefCell<Option<gtk::Label>>,
+        pub rewrite_row: RefCell<Option<adw::ActionRow>>,
+        /// Built once from the config and again when preferences are closed,
+        /// since the destination preview uses it on every keystroke
+        pub rewriter: RefCell<UrlRewriter>,
+        /// Shorten the next link as typed, without the rewrite rules
+        pub keep_original_button: RefCell<Option<gtk::ToggleButton>>,
         pub shorten_button: RefCell<Option<gtk::Button>>,
+        pub expand_button: RefCell<Option<gtk::Button>>,
         pub links_list: RefCell<Option<gtk::ListBox>>,
         pub toast_overlay: RefCell<Option<adw::ToastOverlay>>,
         pub storage: RefCell<Option<HistoryStorage>>,
         pub domains: RefCell<Vec<String>>,
+        // Campaign
+        pub campaign_expander: RefCell<Option<adw::ExpanderRow>>,
+        pub preset_combo: RefCell<Option<adw::ComboRow>>,
+        pub source_entry: RefCell<Option<adw::EntryRow>>,
+        pub medium_entry: RefCell<Option<adw::EntryRow>>,
+        pub campaign_entry: RefCell<Option<adw::EntryRow>>,
+        pub term_entry: RefCell<Option<adw::EntryRow>>,
+        pub content_entry: RefCell<Option<adw::EntryRow>>,
+        pub final_url_row: RefCell<Option<adw::ActionRow>>,
+        pub presets: RefCell<Option<CampaignPresets>>,
+        pub refreshing_presets: Cell<bool>,
+        // Campaign filter
+        pub campaign_filter: RefCell<Option<gtk::DropDown>>,
+        /// Campaigns listed in the filter, after "All Campaigns"
+        pub filter_campaigns: RefCell<Vec<String>>,
+        pub refreshing_filter: Cell<bool>,
+        // Draft
+        /// Domain of the restored draft, applied once the domain list has loaded
+        pub draft_domain: RefCell<Option<String>>,
+        pub draft_source: RefCell<Option<glib::SourceId>>,
+        pub restoring_draft: Cell<bool>,
         // Pagination
         pub current_page: Cell<usize>,
         pub page_label: RefCell<Option<gtk::Label>>,
@@ -74,6 +111,8 @@ impl LinksView {
                 log::error!("Failed to load history: {}", e);
             }
         }
+        imp.rewriter
+            .replace(UrlRewriter::from_config(&Config::load().unwrap_or_default()));
 
         // Toast overlay wraps everything
         let toast_overlay = adw::ToastOverlay::new();
@@ -111,6 +150,18 @@ impl LinksView {
             .build();
         url_entry.add_css_class("url-entry");
 
+        // Before and after, when tracking parameters or rewrite rules change the URL
+        let rewrite_row = adw::ActionRow::builder()
+            .title("Cleaned Up")
+            .visible(false)
+            .build();
+        let keep_original_button = gtk::ToggleButton::builder()
+            .label("Keep Original")
+            .valign(gtk::Align::Center)
+            .build();
+        keep_original_button.set_tooltip_text(Some("Shorten this link as entered"));
+        rewrite_row.add_suffix(&keep_original_button);
+
         let domain_combo = adw::ComboRow::builder()
             .title("Domain")
             .subtitle("Loading domains...")
@@ -121,22 +172,111 @@ impl LinksView {
             .build();
         slug_entry.set_tooltip_text(Some("Leave empty for auto-generated alias"));
 
+        let title_entry = adw::EntryRow::builder()
+            .title("Title")
+            .build();
+        title_entry.set_tooltip_text(Some("Leave empty to use the page title"));
+
         let shorten_button = gtk::Button::builder()
             .label("Shorten URL")
             .css_classes(["see-primary"])
-            .halign(gtk::Align::End)
-            .margin_top(16)
             .build();
 
+        let expand_button = gtk::Button::builder()
+            .label("Expand URL")
+            .build();
+        expand_button.set_tooltip_text(Some("See where a short link redirects to"));
+
         create_group.add(&url_entry);
+        create_group.add(&rewrite_row);
         create_group.add(&domain_combo);
         create_group.add(&slug_entry);
+        create_group.add(&title_entry);
+
+        // Campaign section, tagging the destination with UTM parameters
+        let campaign_expander = adw::ExpanderRow::builder()
+            .title("Campaign")
+            .subtitle("Add UTM parameters to the destination")
+            .show_enable_switch(true)
+            .enable_expansion(false)
+            .build();
+
+        let preset_combo = adw::ComboRow::builder()
+            .title("Preset")
+            .build();
+        let save_preset_button = gtk::Button::builder()
+            .icon_name("document-save-symbolic")
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+        save_preset_button.set_tooltip_text(Some("Save as preset"));
+        let delete_preset_button = gtk::Button::builder()
+            .icon_name("user-trash-symbolic")
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+        delete_preset_button.set_tooltip_text(Some("Delete preset"));
+        preset_combo.add_suffix(&save_preset_button);
+        preset_combo.add_suffix(&delete_preset_button);
+
+        let source_entry = adw::EntryRow::builder()
+            .title("Source (e.g. newsletter)")
+            .build();
+        let medium_entry = adw::EntryRow::builder()
+            .title("Medium (e.g. email)")
+            .build();
+        let campaign_entry = adw::EntryRow::builder()
+            .title("Campaign Name")
+            .build();
+        let term_entry = adw::EntryRow::builder()
+            .title("Term")
+            .build();
+        let content_entry = adw::EntryRow::builder()
+            .title("Content")
+            .build();
+
+        let final_url_row = adw::ActionRow::builder()
+            .title("Final URL")
+            .subtitle_selectable(true)
+            .build();
+        final_url_row.add_css_class("property");
+
+        campaign_expander.add_row(&preset_combo);
+        campaign_expander.add_row(&source_entry);
+        campaign_expander.add_row(&medium_entry);
+        campaign_expander.add_row(&campaign_entry);
+        campaign_expander.add_row(&term_entry);
+        campaign_expander.add_row(&content_entry);
+        campaign_expander.add_row(&final_url_row);
+        create_group.add(&campaign_expander);
+
+        // Why the URL cannot be shortened, updated as it is typed
+        let url_error_label = gtk::Label::builder()
+            .hexpand(true)
+            .xalign(0.0)
+            .wrap(true)
+            .margin_top(16)
+            .visible(false)
+            .css_classes(["caption", "error"])
+            .build();
 
         let button_box = gtk::Box::builder()
             .orientation(gtk::Orientation::Horizontal)
+            .spacing(12)
+            .build();
+        let actions_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(8)
             .halign(gtk::Align::End)
+            .valign(gtk::Align::Start)
+            .hexpand(true)
+            .margin_top(16)
             .build();
-        button_box.append(&shorten_button);
+        actions_box.append(&expand_button);
+        actions_box.append(&shorten_button);
+
+        button_box.append(&url_error_label);
+        button_box.append(&actions_box);
 
         // Recent Links group with header
         let recent_group = adw::PreferencesGroup::builder()
@@ -150,7 +290,20 @@ impl LinksView {
             .css_classes(["flat", "clear-history"])
             .build();
         clear_button.set_tooltip_text(Some("Clear local history"));
-        recent_group.set_header_suffix(Some(&clear_button));
+
+        let campaign_filter = gtk::DropDown::builder()
+            .valign(gtk::Align::Center)
+            .visible(false)
+            .build();
+        campaign_filter.set_tooltip_text(Some("Show links from one campaign"));
+
+        let header_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(4)
+            .build();
+        header_box.append(&campaign_filter);
+        header_box.append(&clear_button);
+        recent_group.set_header_suffix(Some(&header_box));
 
         let links_list = gtk::ListBox::builder()
             .selection_mode(gtk::SelectionMode::None)
@@ -240,7 +393,21 @@ impl LinksView {
         imp.url_entry.replace(Some(url_entry.clone()));
         imp.domain_combo.replace(Some(domain_combo.clone()));
         imp.slug_entry.replace(Some(slug_entry.clone()));
+        imp.title_entry.replace(Some(title_entry.clone()));
+        imp.url_error_label.replace(Some(url_error_label.clone()));
+        imp.rewrite_row.replace(Some(rewrite_row.clone()));
+        imp.keep_original_button.replace(Some(keep_original_button.clone()));
+        imp.campaign_expander.replace(Some(campaign_expander.clone()));
+        imp.preset_combo.replace(Some(preset_combo.clone()));
+        imp.source_entry.replace(Some(source_entry.clone()));
+        imp.medium_entry.replace(Some(medium_entry.clone()));
+        imp.campaign_entry.replace(Some(campaign_entry.clone()));
+        imp.term_entry.replace(Some(term_entry.clone()));
+        imp.content_entry.replace(Some(content_entry.clone()));
+        imp.final_url_row.replace(Some(final_url_row.clone()));
+        imp.campaign_filter.replace(Some(campaign_filter.clone()));
         imp.shorten_button.replace(Some(shorten_button.clone()));
+        imp.expand_button.replace(Some(expand_button.clone()));
         imp.links_list.replace(Some(links_list.clone()));
         imp.toast_overlay.replace(Some(toast_overlay.clone()));
         imp.page_label.replace(Some(page_label.clone()));
@@ -256,6 +423,117 @@ impl LinksView {
             }
         ));
 
+        expand_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.expand_url();
+            }
+        ));
+
+        // Autosave the form as a draft
+        url_entry.connect_changed(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.validate_url();
+                view.update_destination();
+                view.schedule_draft_save();
+            }
+        ));
+        slug_entry.connect_changed(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.schedule_draft_save();
+            }
+        ));
+        title_entry.connect_changed(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.schedule_draft_save();
+            }
+        ));
+        domain_combo.connect_selected_notify(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.schedule_draft_save();
+            }
+        ));
+
+        keep_original_button.connect_toggled(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.update_destination();
+            }
+        ));
+
+        // Keep the tagged URL up to date with the campaign fields
+        campaign_expander.connect_enable_expansion_notify(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.update_destination();
+                view.schedule_draft_save();
+            }
+        ));
+        for entry in [
+            &source_entry,
+            &medium_entry,
+            &campaign_entry,
+            &term_entry,
+            &content_entry,
+        ] {
+            entry.connect_changed(glib::clone!(
+                #[weak(rename_to = view)]
+                self,
+                move |_| {
+                    view.update_destination();
+                    view.schedule_draft_save();
+                }
+            ));
+        }
+
+        // Campaign presets
+        preset_combo.connect_selected_notify(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                if !view.imp().refreshing_presets.get() {
+                    view.apply_preset();
+                }
+            }
+        ));
+        save_preset_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.save_preset();
+            }
+        ));
+        delete_preset_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.delete_preset();
+            }
+        ));
+
+        // Filter history by campaign
+        campaign_filter.connect_selected_notify(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                if !view.imp().refreshing_filter.get() {
+                    view.imp().current_page.set(0);
+                    view.refresh_list();
+                }
+            }
+        ));
+
         // Connect clear history button
         clear_button.connect_clicked(glib::clone!(
             #[weak(rename_to = view)]
@@ -291,6 +569,22 @@ impl LinksView {
         // Load existing links
         self.refresh_list();
 
+        match CampaignPresets::load() {
+            Ok(presets) => {
+                imp.presets.replace(Some(presets));
+            }
+            Err(e) => log::error!("Failed to load campaign presets: {}", e),
+        }
+        self.refresh_presets(None);
+        self.update_destination();
+
+        // Restore the form as it was left
+        match LinkDraft::load() {
+            Ok(Some(draft)) => self.restore_draft(draft),
+            Ok(None) => {}
+            Err(e) => log::error!("Failed to load draft: {}", e),
+        }
+
         // Fetch domains from API
         self.fetch_domains();
     }
@@ -328,8 +622,7 @@ impl LinksView {
     fn clear_local_history(&self) {
         let imp = self.imp();
         if let Some(ref mut storage) = *imp.storage.borrow_mut() {
-            storage.clear_links();
-            if let Err(e) = storage.save() {
+            if let Err(e) = storage.clear_links() {
                 log::error!("Failed to save after clearing history: {}", e);
             }
         }
@@ -373,15 +666,14 @@ impl LinksView {
             combo.set_model(Some(&string_list));
             combo.set_subtitle("");
 
-            // Select default domain from config
+            // Select the draft's domain, or the default domain from config
             let config = Config::load().unwrap_or_default();
-            if let Some(default_domain) = config.default_link_domain() {
-                for (i, domain) in domains.iter().enumerate() {
-                    if domain == default_domain {
-                        combo.set_selected(i as u32);
-                        break;
-                    }
-                }
+            let draft_domain = imp.draft_domain.take();
+            let preferred = draft_domain.as_deref().or(config.default_link_domain());
+            if let Some(position) = preferred.and_then(|p| domains.iter().position(|d| d == p)) {
+                imp.restoring_draft.set(true);
+                combo.set_selected(position as u32);
+                imp.restoring_draft.set(false);
             }
         }
     }
@@ -392,21 +684,41 @@ impl LinksView {
         let url_entry = imp.url_entry.borrow();
         let domain_combo = imp.domain_combo.borrow();
         let slug_entry = imp.slug_entry.borrow();
+        let title_entry = imp.title_entry.borrow();
 
-        let url = url_entry
+        let input = url_entry
             .as_ref()
             .map(|e| e.text().to_string())
             .unwrap_or_default();
-        if url.is_empty() {
+        if input.trim().is_empty() {
             self.show_toast("Please enter a URL");
             return;
         }
+        let url = match UrlNormalizer::normalize(&input) {
+            Ok(url) => self.rewrite(&url),
+            Err(e) => {
+                self.show_toast(&e.to_string());
+                return;
+            }
+        };
+        let campaign = self.campaign();
+        let url = match campaign {
+            Some(ref campaign) => campaign.apply(&url).to_string(),
+            None => url.to_string(),
+        };
+        let campaign = campaign
+            .map(|c| c.name.trim().to_string())
+            .filter(|name| !name.is_empty());
+
+        let config = Config::load().unwrap_or_default();
 
+        // Domains may still be loading, as when a link arrives on the command line
         let domains = imp.domains.borrow();
         let domain = domain_combo
             .as_ref()
             .and_then(|c| domains.get(c.selected() as usize))
             .cloned()
+            .or_else(|| config.default_link_domain().map(String::from))
             .unwrap_or_else(|| "s.ee".to_string());
 
         let slug = slug_entry
@@ -414,8 +726,178 @@ impl LinksView {
             .map(|e| e.text().to_string())
             .filter(|s| !s.is_empty());
 
-        let config = Config::load().unwrap_or_default();
+        let title = title_entry
+            .as_ref()
+            .map(|e| e.text().trim().to_string())
+            .filter(|t| !t.is_empty());
+
+        // An alias asks for a particular link, so only that one counts as the same
+        let existing = imp
+            .storage
+            .borrow()
+            .as_ref()
+            .and_then(|s| s.find_link(&url, &domain).cloned())
+            .filter(|e| slug.as_ref().is_none_or(|s| *s == e.slug));
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            async move {
+                if let Some(existing) = existing {
+                    let reuse = match config.duplicate_links() {
+                        DuplicateLinkPolicy::Ask => {
+                            match view.ask_reuse_link(&existing).await.as_str() {
+                                "reuse" => true,
+                                "create" => false,
+                                _ => return,
+                            }
+                        }
+                        DuplicateLinkPolicy::Reuse => true,
+                        DuplicateLinkPolicy::CreateNew => false,
+                    };
+                    if reuse {
+                        view.clear_form();
+                        view.show_toast("Existing link copied");
+                        if let Some(display) = gdk::Display::default() {
+                            display.clipboard().set_text(&existing.short_url);
+                        }
+                        return;
+                    }
+                }
+
+                view.create_link(config, url, domain, slug, title, campaign);
+            }
+        ));
+    }
+
+    /// Short link domains offered by the server
+    pub fn domains(&self) -> Vec<String> {
+        self.imp().domains.borrow().clone()
+    }
+
+    /// Put a URL in the form unless something has been typed there already
+    pub fn prefill_url(&self, url: &str) -> bool {
+        let Some(entry) = self.imp().url_entry.borrow().clone() else {
+            return false;
+        };
+        if !entry.text().trim().is_empty() {
+            return false;
+        }
+        entry.set_text(url);
+        self.show_toast("Copied URL filled in");
+        true
+    }
+
+    /// Replace the URL in the form, leaving the other fields as they are
+    pub fn set_url(&self, url: &str) {
+        if let Some(ref entry) = *self.imp().url_entry.borrow() {
+            entry.set_text(url);
+        }
+    }
+
+    /// Shorten a URL from elsewhere with the rest of the form as it is
+    pub fn shorten(&self, url: &str) {
+        self.set_url(url);
+        self.shorten_url();
+    }
+
+    /// Follow the redirects of the entered link and show where it ends up.
+    /// The final destination can be put in the form and shortened from there.
+    fn expand_url(&self) {
+        let imp = self.imp();
+        let input = imp
+            .url_entry
+            .borrow()
+            .as_ref()
+            .map(|e| e.text().to_string())
+            .unwrap_or_default();
+        if input.trim().is_empty() {
+            self.show_toast("Please enter a URL");
+            return;
+        }
+        let url = match UrlNormalizer::normalize(&input) {
+            Ok(url) => url,
+            Err(e) => {
+                self.show_toast(&e.to_string());
+                return;
+            }
+        };
+
+        let Some(button) = imp.expand_button.borrow().clone() else {
+            return;
+        };
+        button.set_sensitive(false);
+        button.set_label("Expanding…");
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            async move {
+                let result =
+                    gio::spawn_blocking(move || RedirectTracer::new().map(|t| t.trace(&url))).await;
+                button.set_label("Expand URL");
+                view.validate_url();
+
+                let chain = match result {
+                    Ok(Ok(chain)) => chain,
+                    Ok(Err(e)) => {
+                        view.show_toast(&format!("Error: {}", e));
+                        return;
+                    }
+                    Err(_) => {
+                        view.show_toast("Failed to expand URL");
+                        return;
+                    }
+                };
+
+                let dialog = RedirectDialog::new(&chain);
+                if dialog.choose_future(&view).await != "shorten" {
+                    return;
+                }
+                if let Some(destination) = chain.destination() {
+                    if let Some(ref entry) = *view.imp().url_entry.borrow() {
+                        entry.set_text(destination.as_str());
+                    }
+                    view.shorten_url();
+                }
+            }
+        ));
+    }
+
+    async fn ask_reuse_link(&self, existing: &LinkEntry) -> glib::GString {
+        let shortened = existing
+            .created_at
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M");
+        let dialog = adw::AlertDialog::builder()
+            .heading("Already Shortened")
+            .body(format!(
+                "This URL was shortened to {} on {}. Copy that link or create a new one?",
+                existing.short_url, shortened
+            ))
+            .build();
 
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("create", "Create New Link");
+        dialog.add_response("reuse", "Copy Existing Link");
+        dialog.set_response_appearance("reuse", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("reuse"));
+        dialog.set_close_response("cancel");
+
+        dialog.choose_future(self).await
+    }
+
+    /// Ask the server for a new short link and record it in history
+    fn create_link(
+        &self,
+        config: Config,
+        url: String,
+        domain: String,
+        slug: Option<String>,
+        title: Option<String>,
+        campaign: Option<String>,
+    ) {
+        let fetch_page_info = config.fetch_page_info();
         let request = ApiRequest::ShortenUrl {
             url: url.clone(),
             domain: Some(domain.clone()),
@@ -435,13 +917,17 @@ impl LinksView {
                 if let Ok(response) = receiver.recv().await {
                     match response {
                         ApiResponse::ShortenUrl(Ok(result)) => {
-                            let entry = LinkEntry::new(
+                            let mut entry = LinkEntry::new(
                                 url,
                                 result.data.short_url.clone(),
                                 domain,
                                 result.data.slug.clone(),
-                                None,
+                                title,
                             );
+                            entry.campaign = campaign;
+                            if fetch_page_info {
+                                view.fetch_page_info(&entry);
+                            }
 
                             // Save to storage
                             if let Some(ref mut storage) = *view.imp().storage.borrow_mut() {
@@ -471,8 +957,66 @@ impl LinksView {
         ));
     }
 
+    /// Look up the page behind a new link in the background.
+    /// Its title is kept unless one was typed, and its icon is cached for the site.
+    fn fetch_page_info(&self, entry: &LinkEntry) {
+        let Ok(url) = Url::parse(&entry.original_url) else {
+            return;
+        };
+        let page_url = entry.original_url.clone();
+        let domain = entry.domain.clone();
+        let slug = entry.slug.clone();
+        let keep_title = entry.title.is_none();
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            async move {
+                let result = gio::spawn_blocking(move || -> Result<Option<String>> {
+                    let fetcher = PageFetcher::new()?;
+                    let info = fetcher.fetch_page(&url)?;
+
+                    let favicons = FaviconCache::load()?;
+                    let cached = favicons.get(&page_url).is_some();
+                    if let Some(icon_url) = info.icon_url.filter(|_| !cached) {
+                        let stored = fetcher
+                            .fetch_icon(&icon_url)
+                            .and_then(|bytes| favicons.store(&page_url, &bytes));
+                        if let Err(e) = stored {
+                            log::warn!("Failed to fetch favicon: {}", e);
+                        }
+                    }
+                    Ok(info.title)
+                })
+                .await;
+
+                let title = match result {
+                    Ok(Ok(title)) => title.filter(|_| keep_title),
+                    Ok(Err(e)) => {
+                        log::warn!("Failed to fetch page info: {}", e);
+                        return;
+                    }
+                    Err(_) => {
+                        log::warn!("Failed to fetch page info");
+                        return;
+                    }
+                };
+
+                if let Some(title) = title {
+                    if let Some(ref mut storage) = *view.imp().storage.borrow_mut() {
+                        if let Err(e) = storage.set_link_title(&domain, &slug, &title) {
+                            log::error!("Failed to save link title: {}", e);
+                        }
+                    }
+                }
+                view.refresh_list();
+            }
+        ));
+    }
+
     fn refresh_list(&self) {
         let imp = self.imp();
+        let campaign = self.update_campaign_filter();
 
         let links_list = match imp.links_list.borrow().as_ref() {
             Some(list) => list.clone(),
@@ -486,7 +1030,11 @@ impl LinksView {
 
         // Get links from storage with pagination
         if let Some(ref storage) = *imp.storage.borrow() {
-            let all_links = storage.links();
+            let all_links: Vec<&LinkEntry> = storage
+                .links()
+                .iter()
+                .filter(|l| campaign.is_none() || l.campaign == campaign)
+                .collect();
             let total_items = all_links.len();
             let total_pages = (total_items + ITEMS_PER_PAGE - 1) / ITEMS_PER_PAGE.max(1);
             let current_page = imp.current_page.get();
@@ -504,8 +1052,12 @@ impl LinksView {
             let end = (start + ITEMS_PER_PAGE).min(total_items);
 
             // Add rows for current page
+            let favicons = FaviconCache::load().ok();
             for entry in all_links.iter().skip(start).take(end - start) {
                 let row = LinkRow::new(entry);
+                if let Some(path) = favicons.as_ref().and_then(|f| f.get(&entry.original_url)) {
+                    row.set_favicon(&path);
+                }
                 self.setup_row_actions(&row);
                 links_list.append(&row);
             }
@@ -643,6 +1195,389 @@ impl LinksView {
         if let Some(ref entry) = *imp.slug_entry.borrow() {
             entry.set_text("");
         }
+        if let Some(ref entry) = *imp.title_entry.borrow() {
+            entry.set_text("");
+        }
+        if let Some(ref button) = *imp.keep_original_button.borrow() {
+            button.set_active(false);
+        }
+    }
+
+    /// Flag a URL that cannot be shortened on the entry itself
+    fn validate_url(&self) {
+        let imp = self.imp();
+        let Some(entry) = imp.url_entry.borrow().clone() else {
+            return;
+        };
+
+        let text = entry.text();
+        let error = if text.trim().is_empty() {
+            None
+        } else {
+            UrlNormalizer::normalize(&text).err()
+        };
+
+        if error.is_some() {
+            entry.add_css_class("error");
+        } else {
+            entry.remove_css_class("error");
+        }
+        if let Some(ref label) = *imp.url_error_label.borrow() {
+            label.set_label(&error.as_ref().map(|e| e.to_string()).unwrap_or_default());
+            label.set_visible(error.is_some());
+        }
+        if let Some(ref button) = *imp.shorten_button.borrow() {
+            button.set_sensitive(error.is_none());
+        }
+        if let Some(ref button) = *imp.expand_button.borrow() {
+            button.set_sensitive(error.is_none());
+        }
+    }
+
+    /// Campaign fields as typed, whether or not the section is turned on
+    fn campaign_fields(&self) -> Campaign {
+        let imp = self.imp();
+        let text = |entry: &RefCell<Option<adw::EntryRow>>| {
+            entry
+                .borrow()
+                .as_ref()
+                .map(|e| e.text().to_string())
+                .unwrap_or_default()
+        };
+        Campaign {
+            source: text(&imp.source_entry),
+            medium: text(&imp.medium_entry),
+            name: text(&imp.campaign_entry),
+            term: text(&imp.term_entry),
+            content: text(&imp.content_entry),
+        }
+    }
+
+    fn set_campaign_fields(&self, campaign: &Campaign) {
+        let imp = self.imp();
+        for (entry, value) in [
+            (&imp.source_entry, &campaign.source),
+            (&imp.medium_entry, &campaign.medium),
+            (&imp.campaign_entry, &campaign.name),
+            (&imp.term_entry, &campaign.term),
+            (&imp.content_entry, &campaign.content),
+        ] {
+            if let Some(ref entry) = *entry.borrow() {
+                entry.set_text(value);
+            }
+        }
+    }
+
+    /// Campaign to tag the destination with, if the section is on and filled in
+    fn campaign(&self) -> Option<Campaign> {
+        let enabled = self
+            .imp()
+            .campaign_expander
+            .borrow()
+            .as_ref()
+            .is_some_and(|e| e.enables_expansion());
+        Some(self.campaign_fields()).filter(|c| enabled && !c.is_empty())
+    }
+
+    /// Show the destination as it will be shortened, cleaned up and tagged with the campaign
+    fn update_destination(&self) {
+        let imp = self.imp();
+        let input = imp
+            .url_entry
+            .borrow()
+            .as_ref()
+            .map(|e| e.text().to_string())
+            .unwrap_or_default();
+        let url = UrlNormalizer::normalize(&input).ok();
+        let cleaned = url.as_ref().map(|url| imp.rewriter.borrow().rewrite(url));
+        let keep_original = self.keeps_original();
+
+        // Strike through whichever of the two will not be shortened
+        if let Some(ref row) = *imp.rewrite_row.borrow() {
+            match url.as_ref().zip(cleaned.as_ref()).filter(|(before, after)| before != after) {
+                Some((before, after)) => {
+                    let before = glib::markup_escape_text(before.as_str());
+                    let after = glib::markup_escape_text(after.as_str());
+                    if keep_original {
+                        row.set_subtitle(&format!("{}\n<s>{}</s>", before, after));
+                    } else {
+                        row.set_subtitle(&format!("<s>{}</s>\n{}", before, after));
+                    }
+                    row.set_visible(true);
+                }
+                None => row.set_visible(false),
+            }
+        }
+
+        let rewritten = if keep_original { url } else { cleaned };
+        if let Some(ref row) = *imp.final_url_row.borrow() {
+            let subtitle = match rewritten {
+                Some(url) => self.campaign_fields().apply(&url).to_string(),
+                None => "Enter a URL above".to_string(),
+            };
+            row.set_subtitle(&glib::markup_escape_text(&subtitle));
+        }
+    }
+
+    /// Whether the rewrite rules are skipped for the link being entered
+    fn keeps_original(&self) -> bool {
+        self.imp()
+            .keep_original_button
+            .borrow()
+            .as_ref()
+            .is_some_and(|b| b.is_active())
+    }
+
+    /// URL cleaned by the rewrite rules, unless they are skipped for this link
+    fn rewrite(&self, url: &Url) -> Url {
+        if self.keeps_original() {
+            return url.clone();
+        }
+        self.imp().rewriter.borrow().rewrite(url)
+    }
+
+    /// Pick up changed tracking and rewrite settings
+    pub fn reload_rewriter(&self) {
+        self.imp()
+            .rewriter
+            .replace(UrlRewriter::from_config(&Config::load().unwrap_or_default()));
+        self.update_destination();
+    }
+
+    /// Fill the preset list, selecting the preset with the given name
+    fn refresh_presets(&self, selected: Option<&str>) {
+        let imp = self.imp();
+        let Some(combo) = imp.preset_combo.borrow().clone() else {
+            return;
+        };
+
+        let model = gtk::StringList::new(&["None"]);
+        let mut position = 0;
+        if let Some(ref presets) = *imp.presets.borrow() {
+            for (i, preset) in presets.presets().iter().enumerate() {
+                model.append(&preset.name);
+                if selected == Some(preset.name.as_str()) {
+                    position = i as u32 + 1;
+                }
+            }
+        }
+
+        imp.refreshing_presets.set(true);
+        combo.set_model(Some(&model));
+        combo.set_selected(position);
+        imp.refreshing_presets.set(false);
+    }
+
+    /// Name of the preset chosen in the list
+    fn selected_preset(&self) -> Option<String> {
+        let imp = self.imp();
+        let index = imp.preset_combo.borrow().as_ref()?.selected().checked_sub(1)?;
+        imp.presets
+            .borrow()
+            .as_ref()?
+            .presets()
+            .get(index as usize)
+            .map(|p| p.name.clone())
+    }
+
+    /// Fill the campaign fields from the chosen preset
+    fn apply_preset(&self) {
+        let Some(name) = self.selected_preset() else {
+            return;
+        };
+        let campaign = self.imp().presets.borrow().as_ref().and_then(|presets| {
+            presets
+                .presets()
+                .iter()
+                .find(|p| p.name == name)
+                .map(|p| p.campaign.clone())
+        });
+        if let Some(campaign) = campaign {
+            self.set_campaign_fields(&campaign);
+        }
+    }
+
+    fn save_preset(&self) {
+        let campaign = self.campaign_fields();
+        if campaign.is_empty() {
+            self.show_toast("Fill in the campaign before saving it");
+            return;
+        }
+        let suggested = self
+            .selected_preset()
+            .unwrap_or_else(|| campaign.name.trim().to_string());
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            async move {
+                let Some(name) = ask_name(
+                    &view,
+                    "Save Campaign Preset",
+                    Some("A preset with the same name is replaced."),
+                    &suggested,
+                    "Save",
+                )
+                .await
+                else {
+                    return;
+                };
+                let result = match *view.imp().presets.borrow_mut() {
+                    Some(ref mut presets) => presets.set(&name, campaign),
+                    None => return,
+                };
+                match result {
+                    Ok(()) => view.show_toast("Preset saved"),
+                    Err(e) => view.show_toast(&format!("Error: {}", e)),
+                }
+                view.refresh_presets(Some(&name));
+            }
+        ));
+    }
+
+    fn delete_preset(&self) {
+        let Some(name) = self.selected_preset() else {
+            return;
+        };
+        let result = match *self.imp().presets.borrow_mut() {
+            Some(ref mut presets) => presets.remove(&name),
+            None => return,
+        };
+        if let Err(e) = result {
+            log::error!("Failed to save campaign presets: {}", e);
+        }
+        self.refresh_presets(None);
+    }
+
+    /// List the campaigns in history in the filter and return the one chosen.
+    /// The choice is kept while that campaign is still in history.
+    fn update_campaign_filter(&self) -> Option<String> {
+        let imp = self.imp();
+        let filter = imp.campaign_filter.borrow().clone()?;
+
+        let mut campaigns: Vec<String> = imp
+            .storage
+            .borrow()
+            .as_ref()
+            .map(|s| s.links().iter().filter_map(|l| l.campaign.clone()).collect())
+            .unwrap_or_default();
+        campaigns.sort();
+        campaigns.dedup();
+
+        let selected = (filter.selected() as usize)
+            .checked_sub(1)
+            .and_then(|i| imp.filter_campaigns.borrow().get(i).cloned());
+        if *imp.filter_campaigns.borrow() != campaigns {
+            let model = gtk::StringList::new(&["All Campaigns"]);
+            for campaign in &campaigns {
+                model.append(campaign);
+            }
+            let position = selected
+                .as_ref()
+                .and_then(|s| campaigns.iter().position(|c| c == s))
+                .map_or(0, |i| i + 1);
+
+            imp.refreshing_filter.set(true);
+            filter.set_model(Some(&model));
+            filter.set_selected(position as u32);
+            imp.refreshing_filter.set(false);
+            imp.filter_campaigns.replace(campaigns.clone());
+        }
+
+        filter.set_visible(!campaigns.is_empty());
+        (filter.selected() as usize)
+            .checked_sub(1)
+            .and_then(|i| campaigns.get(i).cloned())
+    }
+
+    fn restore_draft(&self, draft: LinkDraft) {
+        let imp = self.imp();
+        imp.restoring_draft.set(true);
+        if let Some(ref entry) = *imp.url_entry.borrow() {
+            entry.set_text(&draft.url);
+        }
+        if let Some(ref entry) = *imp.slug_entry.borrow() {
+            entry.set_text(&draft.slug);
+        }
+        if let Some(ref entry) = *imp.title_entry.borrow() {
+            entry.set_text(&draft.title);
+        }
+        if let Some(ref campaign) = draft.campaign {
+            self.set_campaign_fields(campaign);
+            if let Some(ref expander) = *imp.campaign_expander.borrow() {
+                expander.set_enable_expansion(true);
+            }
+        }
+        imp.draft_domain.replace(draft.domain);
+        imp.restoring_draft.set(false);
+    }
+
+    /// Save the form shortly after editing pauses
+    fn schedule_draft_save(&self) {
+        let imp = self.imp();
+        if imp.restoring_draft.get() {
+            return;
+        }
+
+        if let Some(source) = imp.draft_source.take() {
+            source.remove();
+        }
+        let source = glib::timeout_add_local_once(
+            Duration::from_millis(500),
+            glib::clone!(
+                #[weak(rename_to = view)]
+                self,
+                move || {
+                    view.imp().draft_source.replace(None);
+                    view.save_draft();
+                }
+            ),
+        );
+        imp.draft_source.replace(Some(source));
+    }
+
+    /// Write the form to the draft file right away
+    pub fn save_draft(&self) {
+        let imp = self.imp();
+        if let Some(source) = imp.draft_source.take() {
+            source.remove();
+        }
+
+        let text = |entry: &RefCell<Option<adw::EntryRow>>| {
+            entry
+                .borrow()
+                .as_ref()
+                .map(|e| e.text().to_string())
+                .unwrap_or_default()
+        };
+
+        // Keep the restored domain until the domain list has loaded
+        let domains = imp.domains.borrow();
+        let domain = if domains.is_empty() {
+            imp.draft_domain.borrow().clone()
+        } else {
+            imp.domain_combo
+                .borrow()
+                .as_ref()
+                .and_then(|c| domains.get(c.selected() as usize))
+                .cloned()
+        };
+
+        let draft = LinkDraft {
+            url: text(&imp.url_entry),
+            slug: text(&imp.slug_entry),
+            title: text(&imp.title_entry),
+            campaign: imp
+                .campaign_expander
+                .borrow()
+                .as_ref()
+                .is_some_and(|e| e.enables_expansion())
+                .then(|| self.campaign_fields()),
+            domain,
+        };
+        if let Err(e) = draft.save() {
+            log::error!("Failed to save draft: {}", e);
+        }
     }
 
     fn show_toast(&self, message: &str) {
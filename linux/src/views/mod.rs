@@ -2,10 +2,51 @@ mod files;
 mod links;
 pub mod preferences;
 mod qr_dialog;
+mod redirect_dialog;
+mod text_viewer;
 mod texts;
+mod upload_review;
 pub mod window;
 
 pub use files::FilesView;
 pub use links::LinksView;
 pub use qr_dialog::QrDialog;
+pub use redirect_dialog::RedirectDialog;
+pub use text_viewer::TextViewerDialog;
 pub use texts::TextsView;
+pub use upload_review::UploadReviewDialog;
+
+use adw::prelude::*;
+
+/// Ask for a name in a dialog with a text entry. Returns the trimmed name,
+/// or `None` when the dialog is cancelled or the name left empty.
+pub(crate) async fn ask_name(
+    parent: &impl IsA<gtk::Widget>,
+    heading: &str,
+    body: Option<&str>,
+    name: &str,
+    action: &str,
+) -> Option<String> {
+    let name_entry = gtk::Entry::builder()
+        .text(name)
+        .activates_default(true)
+        .build();
+
+    let dialog = adw::AlertDialog::builder()
+        .heading(heading)
+        .extra_child(&name_entry)
+        .build();
+    if let Some(body) = body {
+        dialog.set_body(body);
+    }
+    dialog.add_response("cancel", "Cancel");
+    dialog.add_response("save", action);
+    dialog.set_response_appearance("save", adw::ResponseAppearance::Suggested);
+    dialog.set_default_response(Some("save"));
+    dialog.set_close_response("cancel");
+
+    if dialog.choose_future(parent).await != "save" {
+        return None;
+    }
+    Some(name_entry.text().trim().to_string()).filter(|n| !n.is_empty())
+}
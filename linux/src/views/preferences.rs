@@ -1,6 +1,9 @@
 use crate::api::async_bridge::{spawn_api_call, ApiRequest, ApiResponse};
 use crate::application::SeeApplication;
 use crate::config::Config;
+use crate::format::LinkFormat;
+use crate::links::{DuplicateLinkPolicy, FaviconCache};
+use crate::media::{ImageOutputFormat, ThumbnailCache};
 use adw::prelude::*;
 use adw::subclass::prelude::*;
 use gtk::glib;
@@ -22,6 +25,40 @@ mod imp {
         pub default_text_domain_combo: TemplateChild<adw::ComboRow>,
         #[template_child]
         pub default_file_domain_combo: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub watch_clipboard_switch: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub fetch_page_info_switch: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub strip_tracking_switch: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub duplicate_links_combo: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub favicon_cache_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub clear_favicons_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub copy_format_combo: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub review_uploads_switch: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub max_upload_size_spin: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub allowed_types_entry: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub thumbnail_cache_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub clear_thumbnails_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub clipboard_format_combo: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub image_quality_spin: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub max_dimension_spin: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub process_dropped_switch: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub strip_metadata_switch: TemplateChild<adw::SwitchRow>,
         pub link_domains: RefCell<Vec<String>>,
         pub text_domains: RefCell<Vec<String>>,
         pub file_domains: RefCell<Vec<String>>,
@@ -73,6 +110,37 @@ impl SeePreferencesWindow {
         }
 
         imp.base_url_entry.set_text(config.base_url());
+        imp.watch_clipboard_switch.set_active(config.watch_clipboard());
+        imp.fetch_page_info_switch.set_active(config.fetch_page_info());
+        imp.strip_tracking_switch.set_active(config.strip_tracking_params());
+        let duplicate_links = config.duplicate_links();
+        imp.duplicate_links_combo.set_selected(
+            DuplicateLinkPolicy::ALL
+                .iter()
+                .position(|p| *p == duplicate_links)
+                .unwrap_or(0) as u32,
+        );
+        window.update_favicon_cache_size();
+        let copy_format = config.default_copy_format();
+        imp.copy_format_combo.set_selected(
+            LinkFormat::ALL
+                .iter()
+                .position(|f| *f == copy_format)
+                .unwrap_or(0) as u32,
+        );
+        imp.review_uploads_switch.set_active(config.review_uploads());
+        imp.max_upload_size_spin.set_value(config.max_upload_size_mb() as f64);
+        imp.allowed_types_entry.set_text(&config.allowed_upload_types().join(", "));
+        window.update_thumbnail_cache_size();
+        imp.clipboard_format_combo.set_selected(match config.clipboard_image_format() {
+            ImageOutputFormat::Png => 0,
+            ImageOutputFormat::Webp => 1,
+            ImageOutputFormat::Jpeg => 2,
+        });
+        imp.image_quality_spin.set_value(f64::from(config.image_quality()));
+        imp.max_dimension_spin.set_value(f64::from(config.image_max_dimension()));
+        imp.process_dropped_switch.set_active(config.process_dropped_images());
+        imp.strip_metadata_switch.set_active(config.strip_image_metadata());
 
         // Fetch domains from API for each type
         window.fetch_link_domains(config.default_link_domain().map(|s| s.to_string()));
@@ -299,6 +367,218 @@ impl SeePreferencesWindow {
                     }
                 }
             ));
+
+        // Save copy format on change
+        imp.copy_format_combo.connect_selected_notify(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |combo| {
+                let format = LinkFormat::ALL
+                    .get(combo.selected() as usize)
+                    .copied()
+                    .unwrap_or_default();
+                window.save_config(|config| {
+                    config.default_copy_format = Some(format);
+                });
+            }
+        ));
+
+        // Save page info toggle on change
+        imp.fetch_page_info_switch.connect_active_notify(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |switch| {
+                let active = switch.is_active();
+                window.save_config(|config| {
+                    config.fetch_page_info = Some(active);
+                });
+            }
+        ));
+
+        // Save clipboard watching toggle on change
+        imp.watch_clipboard_switch.connect_active_notify(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |switch| {
+                let active = switch.is_active();
+                window.save_config(|config| {
+                    config.watch_clipboard = Some(active);
+                });
+            }
+        ));
+
+        // Save tracking parameter toggle on change
+        imp.strip_tracking_switch.connect_active_notify(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |switch| {
+                let active = switch.is_active();
+                window.save_config(|config| {
+                    config.strip_tracking_params = Some(active);
+                });
+            }
+        ));
+
+        // Save duplicate link handling on change
+        imp.duplicate_links_combo.connect_selected_notify(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |combo| {
+                let policy = DuplicateLinkPolicy::ALL
+                    .get(combo.selected() as usize)
+                    .copied()
+                    .unwrap_or_default();
+                window.save_config(|config| {
+                    config.duplicate_links = Some(policy);
+                });
+            }
+        ));
+
+        // Clear cached favicons
+        imp.clear_favicons_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_| {
+                match FaviconCache::load().and_then(|cache| cache.clear()) {
+                    Ok(()) => window.add_toast(adw::Toast::new("Icon cache cleared")),
+                    Err(e) => log::error!("Failed to clear icon cache: {}", e),
+                }
+                window.update_favicon_cache_size();
+            }
+        ));
+
+        // Save upload review toggle on change
+        imp.review_uploads_switch.connect_active_notify(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |switch| {
+                let active = switch.is_active();
+                window.save_config(|config| {
+                    config.review_uploads = Some(active);
+                });
+            }
+        ));
+
+        // Save upload size limit on change
+        imp.max_upload_size_spin.connect_value_notify(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |spin| {
+                let max_size = spin.value() as u64;
+                window.save_config(|config| {
+                    config.max_upload_size_mb = Some(max_size);
+                });
+            }
+        ));
+
+        // Save allowed upload types on change
+        imp.allowed_types_entry.connect_changed(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |entry| {
+                let types: Vec<String> = entry
+                    .text()
+                    .split(',')
+                    .map(|t| t.trim().to_string())
+                    .filter(|t| !t.is_empty())
+                    .collect();
+                window.save_config(|config| {
+                    config.allowed_upload_types = (!types.is_empty()).then_some(types);
+                });
+            }
+        ));
+
+        // Clear cached thumbnails
+        imp.clear_thumbnails_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_| {
+                match ThumbnailCache::load().and_then(|cache| cache.clear()) {
+                    Ok(()) => window.add_toast(adw::Toast::new("Thumbnail cache cleared")),
+                    Err(e) => log::error!("Failed to clear thumbnail cache: {}", e),
+                }
+                window.update_thumbnail_cache_size();
+            }
+        ));
+
+        // Save pasted image format on change
+        imp.clipboard_format_combo
+            .connect_selected_notify(glib::clone!(
+                #[weak(rename_to = window)]
+                self,
+                move |combo| {
+                    let format = match combo.selected() {
+                        1 => ImageOutputFormat::Webp,
+                        2 => ImageOutputFormat::Jpeg,
+                        _ => ImageOutputFormat::Png,
+                    };
+                    window.save_config(|config| {
+                        config.clipboard_image_format = Some(format);
+                    });
+                }
+            ));
+
+        // Save image quality on change
+        imp.image_quality_spin.connect_value_notify(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |spin| {
+                let quality = spin.value() as u8;
+                window.save_config(|config| {
+                    config.image_quality = Some(quality);
+                });
+            }
+        ));
+
+        // Save maximum image dimension on change
+        imp.max_dimension_spin.connect_value_notify(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |spin| {
+                let max_dimension = spin.value() as u32;
+                window.save_config(|config| {
+                    config.image_max_dimension = Some(max_dimension);
+                });
+            }
+        ));
+
+        // Save dropped image processing toggle on change
+        imp.process_dropped_switch.connect_active_notify(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |switch| {
+                let active = switch.is_active();
+                window.save_config(|config| {
+                    config.process_dropped_images = Some(active);
+                });
+            }
+        ));
+
+        // Save metadata stripping toggle on change
+        imp.strip_metadata_switch.connect_active_notify(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |switch| {
+                let active = switch.is_active();
+                window.save_config(|config| {
+                    config.strip_image_metadata = Some(active);
+                });
+            }
+        ));
+    }
+
+    fn update_favicon_cache_size(&self) {
+        let size = FaviconCache::load().map(|cache| cache.size()).unwrap_or(0);
+        self.imp()
+            .favicon_cache_row
+            .set_subtitle(&format!("{} on disk", glib::format_size(size)));
+    }
+
+    fn update_thumbnail_cache_size(&self) {
+        let size = ThumbnailCache::load().map(|cache| cache.size()).unwrap_or(0);
+        self.imp()
+            .thumbnail_cache_row
+            .set_subtitle(&format!("{} on disk", glib::format_size(size)));
     }
 
     fn save_config<F>(&self, f: F)
@@ -0,0 +1,113 @@
+use crate::links::RedirectChain;
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct RedirectDialog {}
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for RedirectDialog {
+        const NAME: &'static str = "SeeRedirectDialog";
+        type Type = super::RedirectDialog;
+        type ParentType = adw::AlertDialog;
+    }
+
+    impl ObjectImpl for RedirectDialog {}
+    impl WidgetImpl for RedirectDialog {}
+    impl AdwDialogImpl for RedirectDialog {}
+    impl AdwAlertDialogImpl for RedirectDialog {}
+}
+
+glib::wrapper! {
+    pub struct RedirectDialog(ObjectSubclass<imp::RedirectDialog>)
+        @extends gtk::Widget, adw::Dialog, adw::AlertDialog;
+}
+
+impl RedirectDialog {
+    /// Every hop of an expanded link; responds with "shorten" or "close"
+    pub fn new(chain: &RedirectChain) -> Self {
+        let dialog: Self = glib::Object::builder()
+            .property("heading", "Where This Link Goes")
+            .build();
+        dialog.setup_ui(chain);
+        dialog
+    }
+
+    fn setup_ui(&self, chain: &RedirectChain) {
+        let summary = match (chain.destination(), &chain.error) {
+            (Some(destination), _) => {
+                let host = destination.host_str().unwrap_or_default();
+                match chain.redirects() {
+                    0 => format!("This link does not redirect and opens {}.", host),
+                    1 => format!("This link redirects once and ends at {}.", host),
+                    n => format!("This link redirects {} times and ends at {}.", n, host),
+                }
+            }
+            (None, Some(error)) => format!("The redirects could not be followed: {}.", error),
+            (None, None) => "The redirects could not be followed.".to_string(),
+        };
+        let body = if chain.is_suspicious() {
+            format!("{} Check the warnings below before opening it.", summary)
+        } else {
+            summary
+        };
+        self.set_body(&body);
+
+        let hops = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+
+        for (i, hop) in chain.hops.iter().enumerate() {
+            let status = match (hop.status, &hop.location) {
+                (Some(status), Some(location)) if (300..400).contains(&status) => {
+                    format!("{} → {}", status, location)
+                }
+                (Some(status), _) => status.to_string(),
+                (None, _) => "Not requested".to_string(),
+            };
+            let mut subtitle = glib::markup_escape_text(&status).to_string();
+            for warning in &hop.warnings {
+                subtitle.push_str(&format!(
+                    "\n⚠ {}",
+                    glib::markup_escape_text(&warning.description())
+                ));
+            }
+
+            let row = adw::ActionRow::builder()
+                .title(glib::markup_escape_text(hop.url.as_str()).as_str())
+                .subtitle(subtitle.as_str())
+                .title_selectable(true)
+                .build();
+            row.add_prefix(
+                &gtk::Label::builder()
+                    .label((i + 1).to_string())
+                    .css_classes(["dim-label", "numeric"])
+                    .build(),
+            );
+            if !hop.warnings.is_empty() {
+                row.add_css_class("see-hop-warning");
+            }
+            hops.append(&row);
+        }
+
+        let scrolled = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Never)
+            .propagate_natural_height(true)
+            .max_content_height(360)
+            .child(&hops)
+            .build();
+        self.set_extra_child(Some(&scrolled));
+
+        self.add_response("close", "Close");
+        self.add_response("shorten", "Shorten Destination");
+        self.set_response_appearance("shorten", adw::ResponseAppearance::Suggested);
+        self.set_response_enabled("shorten", chain.destination().is_some());
+        self.set_default_response(Some("close"));
+        self.set_close_response("close");
+    }
+}
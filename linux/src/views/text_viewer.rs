@@ -0,0 +1,160 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use glib::subclass::Signal;
+use gtk::{gdk, glib};
+use std::cell::RefCell;
+use std::sync::OnceLock;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct TextViewerDialog {
+        pub text_view: RefCell<Option<gtk::TextView>>,
+        pub toast_overlay: RefCell<Option<adw::ToastOverlay>>,
+        pub content: RefCell<String>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TextViewerDialog {
+        const NAME: &'static str = "SeeTextViewerDialog";
+        type Type = super::TextViewerDialog;
+        type ParentType = adw::Dialog;
+    }
+
+    impl ObjectImpl for TextViewerDialog {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: OnceLock<Vec<Signal>> = OnceLock::new();
+            SIGNALS.get_or_init(|| {
+                vec![
+                    Signal::builder("use-as-draft")
+                        .param_types([String::static_type()])
+                        .build(),
+                ]
+            })
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().setup_ui();
+        }
+    }
+
+    impl WidgetImpl for TextViewerDialog {}
+    impl AdwDialogImpl for TextViewerDialog {}
+}
+
+glib::wrapper! {
+    pub struct TextViewerDialog(ObjectSubclass<imp::TextViewerDialog>)
+        @extends gtk::Widget, adw::Dialog;
+}
+
+impl TextViewerDialog {
+    pub fn new(title: &str, content: &str) -> Self {
+        let dialog: Self = glib::Object::builder()
+            .property("title", title)
+            .property("content-width", 640)
+            .property("content-height", 480)
+            .build();
+        dialog.set_content(content);
+        dialog
+    }
+
+    fn setup_ui(&self) {
+        let imp = self.imp();
+
+        let toolbar_view = adw::ToolbarView::new();
+        let header = adw::HeaderBar::new();
+
+        let copy_button = gtk::Button::builder()
+            .icon_name("edit-copy-symbolic")
+            .tooltip_text("Copy to Clipboard")
+            .build();
+        header.pack_end(&copy_button);
+
+        let draft_button = gtk::Button::builder()
+            .icon_name("document-edit-symbolic")
+            .tooltip_text("Use as New Draft")
+            .build();
+        header.pack_end(&draft_button);
+        toolbar_view.add_top_bar(&header);
+
+        let scrolled = gtk::ScrolledWindow::builder()
+            .hexpand(true)
+            .vexpand(true)
+            .build();
+
+        let text_view = gtk::TextView::builder()
+            .editable(false)
+            .wrap_mode(gtk::WrapMode::Word)
+            .left_margin(16)
+            .right_margin(16)
+            .top_margin(16)
+            .bottom_margin(16)
+            .monospace(true)
+            .build();
+        text_view.add_css_class("see-textview");
+
+        scrolled.set_child(Some(&text_view));
+        toolbar_view.set_content(Some(&scrolled));
+
+        let toast_overlay = adw::ToastOverlay::new();
+        toast_overlay.set_child(Some(&toolbar_view));
+        self.set_child(Some(&toast_overlay));
+
+        imp.text_view.replace(Some(text_view));
+        imp.toast_overlay.replace(Some(toast_overlay));
+
+        copy_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = dialog)]
+            self,
+            move |_| {
+                if let Some(display) = gdk::Display::default() {
+                    display.clipboard().set_text(&dialog.content());
+                    dialog.show_toast("Copied to clipboard");
+                }
+            }
+        ));
+
+        draft_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = dialog)]
+            self,
+            move |_| {
+                dialog.emit_by_name::<()>("use-as-draft", &[&dialog.content()]);
+                dialog.close();
+            }
+        ));
+    }
+
+    /// Called with the content when the user wants to start a new text from it
+    pub fn connect_use_as_draft<F: Fn(&Self, String) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "use-as-draft",
+            false,
+            glib::closure_local!(move |dialog: &Self, content: String| {
+                f(dialog, content);
+            }),
+        )
+    }
+
+    pub fn content(&self) -> String {
+        self.imp().content.borrow().clone()
+    }
+
+    fn set_content(&self, content: &str) {
+        let imp = self.imp();
+        imp.content.replace(content.to_string());
+        if let Some(ref tv) = *imp.text_view.borrow() {
+            tv.buffer().set_text(content);
+        }
+    }
+
+    fn show_toast(&self, message: &str) {
+        if let Some(ref overlay) = *self.imp().toast_overlay.borrow() {
+            overlay.add_toast(adw::Toast::new(message));
+        }
+    }
+}
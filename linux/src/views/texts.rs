@@ -1,13 +1,18 @@
 use crate::api::async_bridge::{spawn_api_call, ApiRequest, ApiResponse};
 use crate::api::TextType;
 use crate::config::Config;
-use crate::storage::{HistoryStorage, TextEntry};
-use crate::views::QrDialog;
-use crate::widgets::TextRow;
+use crate::crypto::{ContentCipher, EncryptionKey};
+use crate::storage::{HistoryStorage, TextDraft, TextDrafts, TextEntry};
+use crate::text::{Language, TextLoader, LANGUAGES};
+use crate::views::{ask_name, QrDialog, TextViewerDialog};
+use crate::widgets::{MarkdownView, TextRow};
 use adw::prelude::*;
 use adw::subclass::prelude::*;
 use gtk::{gdk, gio, glib};
+use sourceview5::prelude::*;
 use std::cell::{Cell, RefCell};
+use std::path::PathBuf;
+use std::time::Duration;
 
 const ITEMS_PER_PAGE: usize = 10;
 
@@ -19,12 +24,25 @@ mod imp {
         pub title_entry: RefCell<Option<adw::EntryRow>>,
         pub domain_combo: RefCell<Option<adw::ComboRow>>,
         pub type_combo: RefCell<Option<adw::ComboRow>>,
-        pub content_view: RefCell<Option<gtk::TextView>>,
+        pub encrypt_switch: RefCell<Option<adw::SwitchRow>>,
+        pub language_combo: RefCell<Option<adw::ComboRow>>,
+        pub content_view: RefCell<Option<sourceview5::View>>,
+        pub preview_switch: RefCell<Option<adw::SwitchRow>>,
+        pub preview_frame: RefCell<Option<gtk::Frame>>,
+        pub preview_view: RefCell<Option<MarkdownView>>,
+        /// Pending re-render of the preview after an edit
+        pub preview_source: RefCell<Option<glib::SourceId>>,
         pub create_button: RefCell<Option<gtk::Button>>,
         pub texts_list: RefCell<Option<gtk::ListBox>>,
         pub toast_overlay: RefCell<Option<adw::ToastOverlay>>,
         pub storage: RefCell<Option<HistoryStorage>>,
         pub domains: RefCell<Vec<String>>,
+        // Drafts
+        pub drafts: RefCell<Option<TextDrafts>>,
+        pub draft_combo: RefCell<Option<adw::ComboRow>>,
+        pub draft_source: RefCell<Option<glib::SourceId>>,
+        /// Set while the form is filled from a draft, so filling it is not saved back
+        pub restoring_draft: Cell<bool>,
         // Pagination
         pub current_page: Cell<usize>,
         pub page_label: RefCell<Option<gtk::Label>>,
@@ -108,6 +126,33 @@ impl TextsView {
             .build();
         create_group.add_css_class("create-card");
 
+        // Named drafts, the editor contents are saved into the selected one
+        let draft_combo = adw::ComboRow::builder()
+            .title("Draft")
+            .build();
+
+        let new_draft_button = gtk::Button::builder()
+            .icon_name("list-add-symbolic")
+            .tooltip_text("New Draft")
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+        let rename_draft_button = gtk::Button::builder()
+            .icon_name("document-edit-symbolic")
+            .tooltip_text("Rename Draft")
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+        let delete_draft_button = gtk::Button::builder()
+            .icon_name("user-trash-symbolic")
+            .tooltip_text("Delete Draft")
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+        draft_combo.add_suffix(&new_draft_button);
+        draft_combo.add_suffix(&rename_draft_button);
+        draft_combo.add_suffix(&delete_draft_button);
+
         let title_entry = adw::EntryRow::builder()
             .title("Title")
             .build();
@@ -128,9 +173,37 @@ impl TextsView {
         type_combo.set_model(Some(&type_list));
         type_combo.set_selected(0); // Default to Plain Text
 
+        // Highlighting language for source code, the first entry turns highlighting off
+        let language_combo = adw::ComboRow::builder()
+            .title("Language")
+            .subtitle("Syntax highlighting")
+            .enable_search(true)
+            .visible(false)
+            .build();
+        let language_names: Vec<&str> = std::iter::once("None")
+            .chain(LANGUAGES.iter().map(|l| l.name))
+            .collect();
+        language_combo.set_model(Some(&gtk::StringList::new(&language_names)));
+
+        let preview_switch = adw::SwitchRow::builder()
+            .title("Preview")
+            .subtitle("Render Markdown below the editor")
+            .active(Config::load().unwrap_or_default().markdown_preview())
+            .visible(false)
+            .build();
+
+        let encrypt_switch = adw::SwitchRow::builder()
+            .title("Encrypt")
+            .subtitle("Only people with the full link can read it. The title is not encrypted.")
+            .build();
+
+        create_group.add(&draft_combo);
         create_group.add(&title_entry);
         create_group.add(&domain_combo);
         create_group.add(&type_combo);
+        create_group.add(&language_combo);
+        create_group.add(&preview_switch);
+        create_group.add(&encrypt_switch);
 
         // Text content editor with improved styling
         let text_frame = gtk::Frame::builder()
@@ -143,7 +216,13 @@ impl TextsView {
             .max_content_height(300)
             .build();
 
-        let content_view = gtk::TextView::builder()
+        let content_buffer = sourceview5::Buffer::new(None);
+        Self::follow_style_scheme(&content_buffer);
+
+        let content_view = sourceview5::View::builder()
+            .buffer(&content_buffer)
+            .auto_indent(true)
+            .tab_width(4)
             .wrap_mode(gtk::WrapMode::Word)
             .left_margin(16)
             .right_margin(16)
@@ -157,6 +236,47 @@ impl TextsView {
         content_scrolled.set_child(Some(&content_view));
         text_frame.set_child(Some(&content_scrolled));
 
+        // Markdown preview, rendered locally as the content changes
+        let preview_frame = gtk::Frame::builder()
+            .margin_top(12)
+            .visible(false)
+            .build();
+        preview_frame.add_css_class("text-editor-frame");
+
+        let preview_scrolled = gtk::ScrolledWindow::builder()
+            .min_content_height(180)
+            .max_content_height(400)
+            .build();
+        let preview_view = MarkdownView::new();
+        preview_scrolled.set_child(Some(&preview_view));
+        preview_frame.set_child(Some(&preview_scrolled));
+
+        // Accept text files dropped onto the editor, ahead of its own text drop handling
+        let drop_target = gtk::DropTarget::new(gio::File::static_type(), gdk::DragAction::COPY);
+        drop_target.set_propagation_phase(gtk::PropagationPhase::Capture);
+        drop_target.connect_drop(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            #[upgrade_or]
+            false,
+            move |_, value, _, _| {
+                if let Ok(file) = value.get::<gio::File>() {
+                    if let Some(path) = file.path() {
+                        view.load_file(path);
+                        return true;
+                    }
+                }
+                false
+            }
+        ));
+        content_view.add_controller(drop_target);
+
+        let open_file_button = gtk::Button::builder()
+            .label("Open File…")
+            .margin_top(16)
+            .build();
+        open_file_button.set_tooltip_text(Some("Load a local text file into the editor"));
+
         let create_button = gtk::Button::builder()
             .label("Create Text")
             .css_classes(["see-primary"])
@@ -167,7 +287,9 @@ impl TextsView {
         let button_box = gtk::Box::builder()
             .orientation(gtk::Orientation::Horizontal)
             .halign(gtk::Align::End)
+            .spacing(12)
             .build();
+        button_box.append(&open_file_button);
         button_box.append(&create_button);
 
         // Recent Texts group with header
@@ -182,7 +304,20 @@ impl TextsView {
             .css_classes(["flat", "clear-history"])
             .build();
         clear_button.set_tooltip_text(Some("Clear local history"));
-        recent_group.set_header_suffix(Some(&clear_button));
+
+        let open_text_button = gtk::Button::builder()
+            .label("Open Text…")
+            .css_classes(["flat"])
+            .build();
+        open_text_button.set_tooltip_text(Some("View a text share from its link"));
+
+        let header_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(4)
+            .build();
+        header_box.append(&open_text_button);
+        header_box.append(&clear_button);
+        recent_group.set_header_suffix(Some(&header_box));
 
         let texts_list = gtk::ListBox::builder()
             .selection_mode(gtk::SelectionMode::None)
@@ -259,6 +394,7 @@ impl TextsView {
 
         content_box.append(&create_group);
         content_box.append(&text_frame);
+        content_box.append(&preview_frame);
         content_box.append(&button_box);
         content_box.append(&recent_group);
         content_box.append(&pagination_box);
@@ -271,9 +407,15 @@ impl TextsView {
 
         // Store references
         imp.title_entry.replace(Some(title_entry.clone()));
+        imp.draft_combo.replace(Some(draft_combo.clone()));
         imp.domain_combo.replace(Some(domain_combo.clone()));
         imp.type_combo.replace(Some(type_combo.clone()));
+        imp.language_combo.replace(Some(language_combo.clone()));
+        imp.encrypt_switch.replace(Some(encrypt_switch.clone()));
         imp.content_view.replace(Some(content_view.clone()));
+        imp.preview_switch.replace(Some(preview_switch.clone()));
+        imp.preview_frame.replace(Some(preview_frame.clone()));
+        imp.preview_view.replace(Some(preview_view.clone()));
         imp.create_button.replace(Some(create_button.clone()));
         imp.texts_list.replace(Some(texts_list.clone()));
         imp.toast_overlay.replace(Some(toast_overlay.clone()));
@@ -290,6 +432,100 @@ impl TextsView {
             }
         ));
 
+        // Keep highlighting in step with the chosen type and language
+        type_combo.connect_selected_notify(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.update_highlighting();
+                view.schedule_draft_save();
+            }
+        ));
+        language_combo.connect_selected_notify(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.update_highlighting();
+                view.schedule_draft_save();
+            }
+        ));
+
+        preview_switch.connect_active_notify(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |switch| {
+                let mut config = Config::load().unwrap_or_default();
+                config.markdown_preview = Some(switch.is_active());
+                if let Err(e) = config.save() {
+                    log::error!("Failed to save config: {}", e);
+                }
+                view.update_preview();
+            }
+        ));
+        content_buffer.connect_changed(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.schedule_preview();
+                view.schedule_draft_save();
+            }
+        ));
+
+        // Autosave the form into the current draft
+        title_entry.connect_changed(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.schedule_draft_save();
+            }
+        ));
+        domain_combo.connect_selected_notify(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.schedule_draft_save();
+            }
+        ));
+        draft_combo.connect_selected_notify(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |combo| {
+                if !view.imp().restoring_draft.get() {
+                    view.switch_draft(combo.selected() as usize);
+                }
+            }
+        ));
+        new_draft_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.new_draft();
+            }
+        ));
+        rename_draft_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.rename_draft();
+            }
+        ));
+        delete_draft_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.delete_draft();
+            }
+        ));
+
+        // Connect open file button
+        open_file_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.show_open_file_dialog();
+            }
+        ));
+
         // Connect clear history button
         clear_button.connect_clicked(glib::clone!(
             #[weak(rename_to = view)]
@@ -299,6 +535,15 @@ impl TextsView {
             }
         ));
 
+        // Connect open text button
+        open_text_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.show_open_text_dialog();
+            }
+        ));
+
         // Connect pagination buttons
         prev_button.connect_clicked(glib::clone!(
             #[weak(rename_to = view)]
@@ -325,6 +570,18 @@ impl TextsView {
         // Load existing texts
         self.refresh_list();
 
+        // Restore the draft left open last time
+        match TextDrafts::load() {
+            Ok(drafts) => {
+                imp.drafts.replace(Some(drafts));
+            }
+            Err(e) => {
+                log::error!("Failed to load drafts: {}", e);
+            }
+        }
+        self.refresh_drafts();
+        self.restore_draft();
+
         // Fetch domains from API
         self.fetch_domains();
     }
@@ -362,8 +619,7 @@ impl TextsView {
     fn clear_local_history(&self) {
         let imp = self.imp();
         if let Some(ref mut storage) = *imp.storage.borrow_mut() {
-            storage.clear_texts();
-            if let Err(e) = storage.save() {
+            if let Err(e) = storage.clear_texts() {
                 log::error!("Failed to save after clearing history: {}", e);
             }
         }
@@ -406,15 +662,19 @@ impl TextsView {
             combo.set_model(Some(&string_list));
             combo.set_subtitle("");
 
-            // Select default domain from config
+            // Select the draft's domain, or the default domain from config
             let config = Config::load().unwrap_or_default();
-            if let Some(default_domain) = config.default_text_domain() {
-                for (i, domain) in domains.iter().enumerate() {
-                    if domain == default_domain {
-                        combo.set_selected(i as u32);
-                        break;
-                    }
-                }
+            let draft_domain = imp
+                .drafts
+                .borrow()
+                .as_ref()
+                .and_then(|d| d.active())
+                .and_then(|d| d.domain.clone());
+            let preferred = draft_domain.as_deref().or(config.default_text_domain());
+            if let Some(position) = preferred.and_then(|p| domains.iter().position(|d| d == p)) {
+                imp.restoring_draft.set(true);
+                combo.set_selected(position as u32);
+                imp.restoring_draft.set(false);
             }
         }
     }
@@ -465,13 +725,41 @@ impl TextsView {
             }
         });
 
+        let encrypt = imp
+            .encrypt_switch
+            .borrow()
+            .as_ref()
+            .is_some_and(|s| s.is_active());
+
+        // The language only means something for content shared in the clear
+        let syntax = if encrypt {
+            None
+        } else {
+            self.selected_syntax().map(String::from)
+        };
+
+        // Encrypt client-side, the key only ever leaves the app in the URL fragment
+        let (upload_content, text_type, encryption_key) = if encrypt {
+            let key = EncryptionKey::generate();
+            match ContentCipher::encrypt_text(&key, &content) {
+                Ok(armored) => (armored, Some(TextType::PlainText), Some(key.to_fragment())),
+                Err(e) => {
+                    self.show_toast(&format!("Error: {}", e));
+                    return;
+                }
+            }
+        } else {
+            (content.clone(), text_type, None)
+        };
+
         let config = Config::load().unwrap_or_default();
 
         let request = ApiRequest::CreateText {
-            content: content.clone(),
+            content: upload_content,
             title: title.clone(),
             domain: domain.clone(),
             text_type,
+            syntax: syntax.clone(),
         };
 
         let receiver = spawn_api_call(config, request);
@@ -499,15 +787,21 @@ impl TextsView {
                             // Build page URL (share page)
                             let page_url = format!("https://{}/{}", domain, result.data.slug);
 
-                            let entry = TextEntry::new(
+                            let mut entry = TextEntry::new(
                                 short_url.clone(),
                                 Some(page_url),
                                 domain,
                                 result.data.slug.clone(),
                                 Some(title),
-                                None,
+                                syntax,
                                 content_preview,
                             );
+                            entry.encryption_key = encryption_key;
+                            let copy_url = if entry.encryption_key.is_some() {
+                                entry.share_url()
+                            } else {
+                                short_url.clone()
+                            };
 
                             // Save to storage
                             if let Some(ref mut storage) = *view.imp().storage.borrow_mut() {
@@ -524,7 +818,7 @@ impl TextsView {
 
                             // Copy to clipboard
                             if let Some(display) = gdk::Display::default() {
-                                display.clipboard().set_text(short_url);
+                                display.clipboard().set_text(&copy_url);
                             }
                         }
                         ApiResponse::CreateText(Err(e)) => {
@@ -604,14 +898,48 @@ impl TextsView {
     fn setup_row_actions(&self, row: &TextRow) {
         let entry = row.entry();
 
-        // Click on row to open share page in browser
-        let entry_for_activate = entry.clone();
-        row.connect_activated(move |_| {
-            if let Some(ref entry) = entry_for_activate {
-                let url = entry.page_url.as_ref().unwrap_or(&entry.url);
-                let _ = gio::AppInfo::launch_default_for_uri(url, None::<&gio::AppLaunchContext>);
+        // Click on row to open share page in browser, encrypted texts open in the app
+        row.connect_activated(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            #[strong]
+            entry,
+            move |_| {
+                if let Some(ref entry) = entry {
+                    if entry.encryption_key.is_some() {
+                        view.open_text(
+                            &entry.share_url(),
+                            entry.title.clone(),
+                            entry.syntax.clone(),
+                        );
+                    } else {
+                        let url = entry.page_url.as_ref().unwrap_or(&entry.url);
+                        let _ = gio::AppInfo::launch_default_for_uri(
+                            url,
+                            None::<&gio::AppLaunchContext>,
+                        );
+                    }
+                }
             }
-        });
+        ));
+
+        // View button, downloads the raw content into the app
+        row.view_button().connect_clicked(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            #[strong]
+            entry,
+            move |_| {
+                if let Some(ref entry) = entry {
+                    let url = if entry.encryption_key.is_some() {
+                        entry.share_url()
+                    } else {
+                        entry.url.clone()
+                    };
+                    view.open_text(&url, entry.title.clone(), entry.syntax.clone());
+                }
+            }
+        ));
 
         // Copy Page URL button
         row.copy_page_button().connect_clicked(glib::clone!(
@@ -622,8 +950,7 @@ impl TextsView {
             move |_| {
                 if let Some(ref entry) = entry {
                     if let Some(display) = gdk::Display::default() {
-                        let url = entry.page_url.as_ref().unwrap_or(&entry.url);
-                        display.clipboard().set_text(url);
+                        display.clipboard().set_text(&entry.share_url());
                         view.show_toast("Share page URL copied");
                     }
                 }
@@ -655,8 +982,7 @@ impl TextsView {
             entry,
             move |_| {
                 if let Some(ref entry) = entry {
-                    let url = entry.page_url.as_ref().unwrap_or(&entry.url);
-                    let dialog = QrDialog::new(url);
+                    let dialog = QrDialog::new(&entry.share_url());
                     if let Some(window) = view.root().and_downcast_ref::<gtk::Window>() {
                         dialog.present(Some(window));
                     }
@@ -721,6 +1047,116 @@ impl TextsView {
         ));
     }
 
+    fn show_open_text_dialog(&self) {
+        let url_entry = gtk::Entry::builder()
+            .placeholder_text("https://p.s.ee/abc")
+            .activates_default(true)
+            .build();
+
+        let dialog = adw::AlertDialog::builder()
+            .heading("Open Text")
+            .body("Paste a text share link. For encrypted texts, include the key after the #")
+            .extra_child(&url_entry)
+            .build();
+
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("open", "Open");
+        dialog.set_response_appearance("open", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("open"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            None,
+            glib::clone!(
+                #[weak(rename_to = view)]
+                self,
+                #[weak]
+                url_entry,
+                move |_, response| {
+                    if response == "open" {
+                        view.open_text(url_entry.text().trim(), None, None);
+                    }
+                }
+            ),
+        );
+
+        if let Some(window) = self.root().and_downcast_ref::<gtk::Window>() {
+            dialog.present(Some(window));
+        }
+    }
+
+    /// Fetch the raw content of a text share and show it in a viewer.
+    /// Encrypted texts are decrypted with the key from the URL fragment.
+    fn open_text(&self, url: &str, title: Option<String>, syntax: Option<String>) {
+        let (share_url, key) = match url.split_once('#') {
+            Some((share_url, fragment)) => match EncryptionKey::from_fragment(fragment) {
+                Ok(key) => (share_url, Some(key)),
+                Err(e) => {
+                    self.show_toast(&format!("Error: {}", e));
+                    return;
+                }
+            },
+            None => (url, None),
+        };
+
+        if !share_url.starts_with("https://") && !share_url.starts_with("http://") {
+            self.show_toast("Not a text share link");
+            return;
+        }
+
+        let config = Config::load().unwrap_or_default();
+        let request = ApiRequest::FetchText {
+            raw_url: raw_url_for(share_url),
+        };
+
+        let receiver = spawn_api_call(config, request);
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            async move {
+                if let Ok(response) = receiver.recv().await {
+                    match response {
+                        ApiResponse::FetchText(Ok(raw)) => {
+                            let content = match key {
+                                Some(ref key) => match ContentCipher::decrypt_text(key, &raw) {
+                                    Ok(content) => content,
+                                    Err(e) => {
+                                        view.show_toast(&format!("Error: {}", e));
+                                        return;
+                                    }
+                                },
+                                None => raw,
+                            };
+                            view.present_text(title, &content, syntax);
+                        }
+                        ApiResponse::FetchText(Err(e)) => {
+                            view.show_toast(&format!("Error: {}", e));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        ));
+    }
+
+    fn present_text(&self, title: Option<String>, content: &str, syntax: Option<String>) {
+        let dialog = TextViewerDialog::new(title.as_deref().unwrap_or("Text"), content);
+        dialog.connect_use_as_draft(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_, content| {
+                let language = syntax.as_deref().and_then(Language::by_id);
+                view.fill_editor(title.as_deref(), &content, language);
+                view.show_toast("Loaded into the editor");
+            }
+        ));
+
+        if let Some(window) = self.root().and_downcast_ref::<gtk::Window>() {
+            dialog.present(Some(window));
+        }
+    }
+
     fn clear_form(&self) {
         let imp = self.imp();
         if let Some(ref entry) = *imp.title_entry.borrow() {
@@ -731,6 +1167,452 @@ impl TextsView {
         }
     }
 
+    /// Language of the content, from the chosen type and language picker
+    fn selected_syntax(&self) -> Option<&'static str> {
+        let imp = self.imp();
+        match imp.type_combo.borrow().as_ref().map(|c| c.selected()) {
+            Some(1) => imp
+                .language_combo
+                .borrow()
+                .as_ref()
+                .and_then(|c| c.selected().checked_sub(1))
+                .and_then(|i| LANGUAGES.get(i as usize))
+                .map(|l| l.id),
+            Some(2) => Some("markdown"),
+            _ => None,
+        }
+    }
+
+    fn update_highlighting(&self) {
+        let imp = self.imp();
+        let syntax = self.selected_syntax();
+        let selected_type = imp.type_combo.borrow().as_ref().map_or(0, |c| c.selected());
+        let is_plain_text = selected_type == 0;
+
+        if let Some(ref combo) = *imp.language_combo.borrow() {
+            combo.set_visible(selected_type == 1);
+        }
+        if let Some(ref switch) = *imp.preview_switch.borrow() {
+            switch.set_visible(selected_type == 2);
+        }
+        self.update_preview();
+
+        if let Some(ref view) = *imp.content_view.borrow() {
+            view.set_show_line_numbers(!is_plain_text);
+            view.set_highlight_current_line(!is_plain_text);
+
+            if let Ok(buffer) = view.buffer().downcast::<sourceview5::Buffer>() {
+                let language =
+                    syntax.and_then(|id| sourceview5::LanguageManager::default().language(id));
+                buffer.set_language(language.as_ref());
+            }
+        }
+    }
+
+    /// Show the Markdown preview when it is wanted, rendering the current content
+    fn update_preview(&self) {
+        let imp = self.imp();
+        let is_markdown = imp.type_combo.borrow().as_ref().is_some_and(|c| c.selected() == 2);
+        let enabled = imp.preview_switch.borrow().as_ref().is_some_and(|s| s.is_active());
+
+        if let Some(ref frame) = *imp.preview_frame.borrow() {
+            frame.set_visible(is_markdown && enabled);
+        }
+        if is_markdown && enabled {
+            self.render_preview();
+        }
+    }
+
+    /// Re-render the preview shortly after typing pauses
+    fn schedule_preview(&self) {
+        let imp = self.imp();
+        if !imp.preview_frame.borrow().as_ref().is_some_and(|f| f.is_visible()) {
+            return;
+        }
+
+        if let Some(source) = imp.preview_source.take() {
+            source.remove();
+        }
+        let source = glib::timeout_add_local_once(
+            Duration::from_millis(150),
+            glib::clone!(
+                #[weak(rename_to = view)]
+                self,
+                move || {
+                    view.imp().preview_source.replace(None);
+                    view.render_preview();
+                }
+            ),
+        );
+        imp.preview_source.replace(Some(source));
+    }
+
+    fn render_preview(&self) {
+        let imp = self.imp();
+        let content = imp
+            .content_view
+            .borrow()
+            .as_ref()
+            .map(|tv| {
+                let buffer = tv.buffer();
+                let (start, end) = buffer.bounds();
+                buffer.text(&start, &end, true).to_string()
+            })
+            .unwrap_or_default();
+
+        if let Some(ref preview) = *imp.preview_view.borrow() {
+            preview.set_markdown(&content);
+        }
+    }
+
+    /// Use the light or dark highlighting scheme matching the app
+    fn follow_style_scheme(buffer: &sourceview5::Buffer) {
+        let style_manager = adw::StyleManager::default();
+        let apply = |buffer: &sourceview5::Buffer, dark: bool| {
+            let id = if dark { "Adwaita-dark" } else { "Adwaita" };
+            let scheme = sourceview5::StyleSchemeManager::default().scheme(id);
+            buffer.set_style_scheme(scheme.as_ref());
+        };
+
+        apply(buffer, style_manager.is_dark());
+        style_manager.connect_dark_notify(glib::clone!(
+            #[weak]
+            buffer,
+            move |manager| {
+                apply(&buffer, manager.is_dark());
+            }
+        ));
+    }
+
+    fn show_open_file_dialog(&self) {
+        let dialog = gtk::FileDialog::builder()
+            .title("Open Text File")
+            .build();
+
+        dialog.open(
+            self.root().and_downcast_ref::<gtk::Window>(),
+            None::<&gio::Cancellable>,
+            glib::clone!(
+                #[weak(rename_to = view)]
+                self,
+                move |result| {
+                    if let Ok(file) = result {
+                        if let Some(path) = file.path() {
+                            view.load_file(path);
+                        }
+                    }
+                }
+            ),
+        );
+    }
+
+    /// Replace the editor contents with a local text file
+    fn load_file(&self, path: PathBuf) {
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            async move {
+                let loaded = match gio::spawn_blocking(move || TextLoader::load(&path)).await {
+                    Ok(Ok(loaded)) => loaded,
+                    Ok(Err(e)) => {
+                        view.show_toast(&e.to_string());
+                        return;
+                    }
+                    Err(_) => {
+                        view.show_toast("Failed to open file");
+                        return;
+                    }
+                };
+
+                view.fill_editor(Some(&loaded.title), &loaded.content, loaded.language);
+
+                let mut message = format!("Opened {}", loaded.title);
+                if let Some(language) = loaded.language {
+                    message.push_str(&format!(" as {}", language.name));
+                }
+                if loaded.encoding != "UTF-8" {
+                    message.push_str(&format!(" ({})", loaded.encoding));
+                }
+                view.show_toast(&message);
+            }
+        ));
+    }
+
+    /// Show the saved drafts in the picker, with the active one selected
+    fn refresh_drafts(&self) {
+        let imp = self.imp();
+        let drafts = imp.drafts.borrow();
+        let (Some(drafts), Some(combo)) = (drafts.as_ref(), imp.draft_combo.borrow().clone())
+        else {
+            return;
+        };
+
+        let names: Vec<&str> = drafts.drafts().iter().map(|d| d.name.as_str()).collect();
+        let active = drafts
+            .active()
+            .and_then(|a| drafts.drafts().iter().position(|d| d.name == a.name))
+            .unwrap_or(0);
+
+        imp.restoring_draft.set(true);
+        combo.set_model(Some(&gtk::StringList::new(&names)));
+        combo.set_selected(active as u32);
+        imp.restoring_draft.set(false);
+    }
+
+    /// Fill the form from the active draft
+    fn restore_draft(&self) {
+        let imp = self.imp();
+        let Some(draft) = imp.drafts.borrow().as_ref().and_then(|d| d.active().cloned()) else {
+            return;
+        };
+
+        imp.restoring_draft.set(true);
+        let language = draft.syntax.as_deref().and_then(Language::by_id);
+        self.fill_editor(Some(&draft.title), &draft.content, language);
+        if let Some(ref combo) = *imp.type_combo.borrow() {
+            combo.set_selected(match draft.text_type {
+                TextType::PlainText => 0,
+                TextType::SourceCode => 1,
+                TextType::Markdown => 2,
+            });
+        }
+
+        let domains = imp.domains.borrow();
+        let position = draft
+            .domain
+            .as_ref()
+            .and_then(|domain| domains.iter().position(|d| d == domain));
+        if let (Some(combo), Some(position)) = (&*imp.domain_combo.borrow(), position) {
+            combo.set_selected(position as u32);
+        }
+        imp.restoring_draft.set(false);
+    }
+
+    /// Save the form into the current draft shortly after editing pauses
+    fn schedule_draft_save(&self) {
+        let imp = self.imp();
+        if imp.restoring_draft.get() {
+            return;
+        }
+
+        if let Some(source) = imp.draft_source.take() {
+            source.remove();
+        }
+        let source = glib::timeout_add_local_once(
+            Duration::from_millis(500),
+            glib::clone!(
+                #[weak(rename_to = view)]
+                self,
+                move || {
+                    view.imp().draft_source.replace(None);
+                    view.save_draft();
+                }
+            ),
+        );
+        imp.draft_source.replace(Some(source));
+    }
+
+    /// Write the form into the current draft right away
+    pub fn save_draft(&self) {
+        let imp = self.imp();
+        if let Some(source) = imp.draft_source.take() {
+            source.remove();
+        }
+
+        let content = imp
+            .content_view
+            .borrow()
+            .as_ref()
+            .map(|tv| {
+                let buffer = tv.buffer();
+                let (start, end) = buffer.bounds();
+                buffer.text(&start, &end, true).to_string()
+            })
+            .unwrap_or_default();
+        let title = imp
+            .title_entry
+            .borrow()
+            .as_ref()
+            .map(|e| e.text().to_string())
+            .unwrap_or_default();
+        let text_type = match imp.type_combo.borrow().as_ref().map(|c| c.selected()) {
+            Some(1) => TextType::SourceCode,
+            Some(2) => TextType::Markdown,
+            _ => TextType::PlainText,
+        };
+
+        let mut drafts = imp.drafts.borrow_mut();
+        let Some(drafts) = drafts.as_mut() else {
+            return;
+        };
+
+        // Keep the saved domain until the domain list has loaded
+        let domains = imp.domains.borrow();
+        let domain = if domains.is_empty() {
+            drafts.active().and_then(|d| d.domain.clone())
+        } else {
+            imp.domain_combo
+                .borrow()
+                .as_ref()
+                .and_then(|c| domains.get(c.selected() as usize))
+                .cloned()
+        };
+
+        let draft = TextDraft {
+            title,
+            content,
+            text_type,
+            syntax: self.selected_syntax().map(String::from),
+            domain,
+            ..TextDraft::new(String::new())
+        };
+        if let Err(e) = drafts.update_active(draft) {
+            log::error!("Failed to save draft: {}", e);
+        }
+    }
+
+    fn switch_draft(&self, index: usize) {
+        self.save_draft();
+
+        let result = {
+            let mut drafts = self.imp().drafts.borrow_mut();
+            let Some(drafts) = drafts.as_mut() else {
+                return;
+            };
+            let Some(name) = drafts.drafts().get(index).map(|d| d.name.clone()) else {
+                return;
+            };
+            drafts.set_active(&name)
+        };
+
+        if let Err(e) = result {
+            log::error!("Failed to save drafts: {}", e);
+        }
+        self.restore_draft();
+    }
+
+    fn new_draft(&self) {
+        let suggestion = self
+            .imp()
+            .drafts
+            .borrow()
+            .as_ref()
+            .map(|d| d.unique_name("Draft"))
+            .unwrap_or_default();
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            async move {
+                let Some(name) = ask_name(&view, "New Draft", None, &suggestion, "Create").await
+                else {
+                    return;
+                };
+
+                view.save_draft();
+                let result = match *view.imp().drafts.borrow_mut() {
+                    Some(ref mut drafts) => drafts.create(&name),
+                    None => return,
+                };
+                match result {
+                    Ok(()) => {
+                        view.refresh_drafts();
+                        view.restore_draft();
+                    }
+                    Err(e) => view.show_toast(&e.to_string()),
+                }
+            }
+        ));
+    }
+
+    fn rename_draft(&self) {
+        let current = self
+            .imp()
+            .drafts
+            .borrow()
+            .as_ref()
+            .and_then(|d| d.active().map(|a| a.name.clone()))
+            .unwrap_or_default();
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            async move {
+                let Some(name) = ask_name(&view, "Rename Draft", None, &current, "Rename").await
+                else {
+                    return;
+                };
+
+                let result = match *view.imp().drafts.borrow_mut() {
+                    Some(ref mut drafts) => drafts.rename_active(&name),
+                    None => return,
+                };
+                match result {
+                    Ok(()) => view.refresh_drafts(),
+                    Err(e) => view.show_toast(&e.to_string()),
+                }
+            }
+        ));
+    }
+
+    fn delete_draft(&self) {
+        let dialog = adw::AlertDialog::builder()
+            .heading("Delete Draft?")
+            .body("The title and content of this draft will be lost.")
+            .build();
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("delete", "Delete");
+        dialog.set_response_appearance("delete", adw::ResponseAppearance::Destructive);
+        dialog.set_default_response(Some("cancel"));
+        dialog.set_close_response("cancel");
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            async move {
+                if dialog.choose_future(&view).await != "delete" {
+                    return;
+                }
+
+                if let Some(source) = view.imp().draft_source.take() {
+                    source.remove();
+                }
+                let result = match *view.imp().drafts.borrow_mut() {
+                    Some(ref mut drafts) => drafts.remove_active(),
+                    None => return,
+                };
+                if let Err(e) = result {
+                    log::error!("Failed to save drafts: {}", e);
+                }
+                view.refresh_drafts();
+                view.restore_draft();
+            }
+        ));
+    }
+
+    /// Replace the editor contents, choosing the type and language to match
+    fn fill_editor(&self, title: Option<&str>, content: &str, language: Option<&Language>) {
+        let imp = self.imp();
+        if let Some(ref entry) = *imp.title_entry.borrow() {
+            entry.set_text(title.unwrap_or(""));
+        }
+        if let Some(ref tv) = *imp.content_view.borrow() {
+            tv.buffer().set_text(content);
+        }
+
+        let position = language.and_then(|language| LANGUAGES.iter().position(|l| l == language));
+        if let (Some(combo), Some(position)) = (&*imp.language_combo.borrow(), position) {
+            combo.set_selected(position as u32 + 1);
+        }
+        if let Some(ref combo) = *imp.type_combo.borrow() {
+            combo.set_selected(match language.map(Language::text_type).unwrap_or_default() {
+                TextType::PlainText => 0,
+                TextType::SourceCode => 1,
+                TextType::Markdown => 2,
+            });
+        }
+    }
+
     fn show_toast(&self, message: &str) {
         if let Some(ref overlay) = *self.imp().toast_overlay.borrow() {
             let toast = adw::Toast::new(message);
@@ -739,6 +1621,16 @@ impl TextsView {
     }
 }
 
+/// Raw content URL for a text share page URL
+fn raw_url_for(share_url: &str) -> String {
+    let url = share_url.trim().trim_end_matches('/');
+    if url.ends_with("/raw") {
+        url.to_string()
+    } else {
+        format!("{}/raw", url)
+    }
+}
+
 impl Default for TextsView {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,141 @@
+use crate::media::{mime_label, FileInfo};
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+use std::cell::RefCell;
+use std::path::Path;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct UploadReviewDialog {
+        pub name_row: RefCell<Option<adw::EntryRow>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for UploadReviewDialog {
+        const NAME: &'static str = "SeeUploadReviewDialog";
+        type Type = super::UploadReviewDialog;
+        type ParentType = adw::AlertDialog;
+    }
+
+    impl ObjectImpl for UploadReviewDialog {}
+    impl WidgetImpl for UploadReviewDialog {}
+    impl AdwDialogImpl for UploadReviewDialog {}
+    impl AdwAlertDialogImpl for UploadReviewDialog {}
+}
+
+glib::wrapper! {
+    pub struct UploadReviewDialog(ObjectSubclass<imp::UploadReviewDialog>)
+        @extends gtk::Widget, adw::Dialog, adw::AlertDialog;
+}
+
+impl UploadReviewDialog {
+    /// Confirmation for a file about to be uploaded; responds with "upload" or "cancel"
+    pub fn new(path: &Path, size: u64, info: &FileInfo) -> Self {
+        let dialog: Self = glib::Object::builder()
+            .property("heading", "Upload File?")
+            .build();
+        dialog.setup_ui(path, size, info);
+        dialog
+    }
+
+    fn setup_ui(&self, path: &Path, size: u64, info: &FileInfo) {
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(12)
+            .build();
+
+        if info.mime_type.as_deref().is_some_and(|m| m.starts_with("image/")) {
+            let preview = gtk::Picture::builder()
+                .content_fit(gtk::ContentFit::Contain)
+                .height_request(200)
+                .can_shrink(true)
+                .build();
+            preview.set_filename(Some(path));
+            content.append(&preview);
+        }
+
+        let details = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+
+        let filename = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let name_row = adw::EntryRow::builder()
+            .title("Name")
+            .text(filename.as_str())
+            .build();
+        details.append(&name_row);
+
+        let size_row = adw::ActionRow::builder()
+            .title("Size")
+            .subtitle(glib::format_size(size).as_str())
+            .build();
+        size_row.add_css_class("property");
+        details.append(&size_row);
+
+        let type_row = adw::ActionRow::builder()
+            .title("Type")
+            .subtitle(Self::describe_type(info))
+            .build();
+        type_row.add_css_class("property");
+        details.append(&type_row);
+
+        content.append(&details);
+        self.set_extra_child(Some(&content));
+
+        self.add_response("cancel", "Cancel");
+        self.add_response("upload", "Upload");
+        self.set_response_appearance("upload", adw::ResponseAppearance::Suggested);
+        self.set_default_response(Some("upload"));
+        self.set_close_response("cancel");
+
+        // The name becomes a path component, so keep it a plain file name
+        name_row.connect_changed(glib::clone!(
+            #[weak(rename_to = dialog)]
+            self,
+            move |row| {
+                let name = row.text();
+                let valid = !name.trim().is_empty()
+                    && name.as_str() != "."
+                    && name.as_str() != ".."
+                    && !name.contains(['/', '\0']);
+                dialog.set_response_enabled("upload", valid);
+            }
+        ));
+
+        self.imp().name_row.replace(Some(name_row));
+    }
+
+    /// File name chosen for the upload
+    pub fn filename(&self) -> String {
+        self.imp()
+            .name_row
+            .borrow()
+            .as_ref()
+            .map(|row| row.text().trim().to_string())
+            .unwrap_or_default()
+    }
+
+    fn describe_type(info: &FileInfo) -> String {
+        let Some(ref mime_type) = info.mime_type else {
+            return "Unknown".to_string();
+        };
+
+        let mut description = mime_label(mime_type);
+        if let (Some(width), Some(height)) = (info.width, info.height) {
+            description.push_str(&format!(" · {}×{}", width, height));
+        }
+        match info.page_count {
+            Some(1) => description.push_str(" · 1 page"),
+            Some(pages) => description.push_str(&format!(" · {} pages", pages)),
+            None => {}
+        }
+        description
+    }
+}
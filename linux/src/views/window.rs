@@ -1,8 +1,22 @@
 use crate::application::SeeApplication;
+use crate::config::Config;
+use crate::links::UrlNormalizer;
+use crate::storage::HistoryStorage;
 use crate::views::{FilesView, LinksView, TextsView};
 use adw::prelude::*;
 use adw::subclass::prelude::*;
-use gtk::{gio, glib};
+use gtk::{gdk, gio, glib};
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use url::Url;
+
+/// Something copied in another app that can be shortened or uploaded
+#[derive(Debug, Clone)]
+pub enum ClipboardOffer {
+    Url(Url),
+    Image,
+}
 
 mod imp {
     use super::*;
@@ -18,6 +32,21 @@ mod imp {
         pub texts_page: TemplateChild<gtk::Box>,
         #[template_child]
         pub files_page: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub clipboard_banner: TemplateChild<adw::Banner>,
+        pub links_view: RefCell<Option<LinksView>>,
+        pub texts_view: RefCell<Option<TextsView>>,
+        pub files_view: RefCell<Option<FilesView>>,
+        // Clipboard watching
+        /// What the banner offers to do, if it is shown
+        pub clipboard_offer: RefCell<Option<ClipboardOffer>>,
+        /// Set when the clipboard changes, cleared once its content has been looked at
+        pub clipboard_changed: Cell<bool>,
+        /// The clipboard preference, read when the window opens and preferences close
+        pub watch_clipboard: Cell<bool>,
+        /// Configured and previously used domains, lowercase, so copied short
+        /// links are not offered again
+        pub own_domains: RefCell<HashSet<String>>,
     }
 
     #[glib::object_subclass]
@@ -42,6 +71,8 @@ mod imp {
             let obj = self.obj();
             obj.setup_views();
             obj.setup_actions();
+            obj.load_clipboard_settings();
+            obj.setup_clipboard_watch();
             obj.load_window_state();
         }
     }
@@ -52,6 +83,7 @@ mod imp {
         fn close_request(&self) -> glib::Propagation {
             let obj = self.obj();
             obj.save_window_state();
+            obj.save_drafts();
             glib::Propagation::Proceed
         }
     }
@@ -79,14 +111,17 @@ impl SeeWindow {
         // Links View
         let links_view = LinksView::new();
         imp.links_page.append(&links_view);
+        imp.links_view.replace(Some(links_view));
 
         // Texts View
         let texts_view = TextsView::new();
         imp.texts_page.append(&texts_view);
+        imp.texts_view.replace(Some(texts_view));
 
         // Files View
         let files_view = FilesView::new();
         imp.files_page.append(&files_view);
+        imp.files_view.replace(Some(files_view));
     }
 
     fn setup_actions(&self) {
@@ -116,6 +151,233 @@ impl SeeWindow {
         self.set_help_overlay(Some(&shortcuts_window));
     }
 
+    /// Look at the clipboard whenever the window is focused after it changed
+    fn setup_clipboard_watch(&self) {
+        let imp = self.imp();
+
+        // Whatever is on the clipboard at startup is worth one look
+        imp.clipboard_changed.set(true);
+        self.clipboard().connect_changed(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |_| {
+                window.imp().clipboard_changed.set(true);
+            }
+        ));
+
+        self.connect_is_active_notify(|window| {
+            if window.is_active() {
+                window.check_clipboard();
+            }
+        });
+
+        imp.clipboard_banner.connect_button_clicked(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            move |banner| {
+                banner.set_revealed(false);
+                if let Some(offer) = window.imp().clipboard_offer.take() {
+                    window.accept_clipboard_offer(offer);
+                }
+            }
+        ));
+    }
+
+    fn check_clipboard(&self) {
+        let imp = self.imp();
+        if !imp.clipboard_changed.replace(false) {
+            return;
+        }
+        if !imp.watch_clipboard.get() {
+            return;
+        }
+
+        // An offer for content that has since been replaced is stale
+        imp.clipboard_banner.set_revealed(false);
+        imp.clipboard_offer.replace(None);
+
+        // Copied from this app, like a short link that was just created
+        let clipboard = self.clipboard();
+        if clipboard.is_local() {
+            return;
+        }
+
+        let formats = clipboard.formats();
+        // Other apps offer image MIME types, which GDK can turn into a texture
+        if formats
+            .union_deserialize_gtypes()
+            .contain_gtype(gdk::Texture::static_type())
+        {
+            self.offer(ClipboardOffer::Image);
+            return;
+        }
+        if !formats.contain_mime_type("text/plain") {
+            return;
+        }
+
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = window)]
+            self,
+            async move {
+                let Ok(Some(text)) = clipboard.read_text_future().await else {
+                    return;
+                };
+                let Some(url) = Self::copied_url(&text) else {
+                    return;
+                };
+                if window.is_own_url(&url) {
+                    return;
+                }
+
+                // Straight into an empty form when it is on screen, otherwise ask first
+                let imp = window.imp();
+                let on_links = imp.view_stack.visible_child_name().as_deref() == Some("links");
+                let prefilled = on_links
+                    && imp
+                        .links_view
+                        .borrow()
+                        .as_ref()
+                        .is_some_and(|view| view.prefill_url(url.as_str()));
+                if !prefilled {
+                    window.offer(ClipboardOffer::Url(url));
+                }
+            }
+        ));
+    }
+
+    /// A copied URL, when the text is one link and nothing else
+    fn copied_url(text: &str) -> Option<Url> {
+        let text = text.trim();
+        let lower = text.to_ascii_lowercase();
+        let has_scheme = lower.starts_with("http://") || lower.starts_with("https://");
+        if !has_scheme || text.contains(char::is_whitespace) {
+            return None;
+        }
+        UrlNormalizer::normalize(text).ok()
+    }
+
+    /// Read what the clipboard watch needs once, rather than every time the window is focused
+    fn load_clipboard_settings(&self) {
+        let imp = self.imp();
+        let config = Config::load().unwrap_or_default();
+        imp.watch_clipboard.set(config.watch_clipboard());
+
+        let mut domains: HashSet<String> = [
+            &config.default_link_domain,
+            &config.default_text_domain,
+            &config.default_file_domain,
+        ]
+        .into_iter()
+        .flatten()
+        .map(|d| d.to_ascii_lowercase())
+        .collect();
+        if let Ok(history) = HistoryStorage::load() {
+            let links = history.links().iter().map(|e| &e.domain);
+            let texts = history.texts().iter().map(|e| &e.domain);
+            let files = history.files().iter().map(|e| &e.domain);
+            domains.extend(
+                links
+                    .chain(texts)
+                    .chain(files)
+                    .map(|d| d.to_ascii_lowercase()),
+            );
+        }
+        imp.own_domains.replace(domains);
+    }
+
+    /// Links on one of our own short domains are not offered for shortening again
+    fn is_own_url(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str().map(|h| h.to_ascii_lowercase()) else {
+            return false;
+        };
+
+        let imp = self.imp();
+        if imp.own_domains.borrow().contains(&host) {
+            return true;
+        }
+        // Domains the server offers, which load after the window opens
+        imp.links_view
+            .borrow()
+            .as_ref()
+            .is_some_and(|view| view.domains().iter().any(|d| d.eq_ignore_ascii_case(&host)))
+    }
+
+    fn offer(&self, offer: ClipboardOffer) {
+        let imp = self.imp();
+        let (title, button) = match offer {
+            ClipboardOffer::Url(_) => ("Shorten copied URL?", "Shorten"),
+            ClipboardOffer::Image => ("Upload copied image?", "Upload"),
+        };
+        imp.clipboard_banner.set_title(title);
+        imp.clipboard_banner.set_button_label(Some(button));
+        imp.clipboard_banner.set_revealed(true);
+        imp.clipboard_offer.replace(Some(offer));
+    }
+
+    fn accept_clipboard_offer(&self, offer: ClipboardOffer) {
+        let imp = self.imp();
+        match offer {
+            ClipboardOffer::Url(url) => {
+                imp.view_stack.set_visible_child_name("links");
+                if let Some(ref view) = *imp.links_view.borrow() {
+                    view.shorten(url.as_str());
+                }
+            }
+            ClipboardOffer::Image => {
+                imp.view_stack.set_visible_child_name("files");
+                if let Some(ref view) = *imp.files_view.borrow() {
+                    view.try_paste_image();
+                }
+            }
+        }
+    }
+
+    /// Switch to the "links", "texts" or "files" tab
+    pub fn show_page(&self, name: &str) {
+        self.imp().view_stack.set_visible_child_name(name);
+    }
+
+    /// Put a link passed to the app in the Links form, and shorten it if asked to
+    pub fn open_url(&self, url: &str, shorten: bool) {
+        self.show_page("links");
+        if let Some(ref view) = *self.imp().links_view.borrow() {
+            if shorten {
+                view.shorten(url);
+            } else {
+                view.set_url(url);
+            }
+        }
+    }
+
+    /// Refresh what the views keep from the config, after preferences are closed
+    pub fn reload_preferences(&self) {
+        if let Some(ref view) = *self.imp().links_view.borrow() {
+            view.reload_rewriter();
+        }
+        self.load_clipboard_settings();
+    }
+
+    /// Upload files passed to the app through the Files view
+    pub fn upload_files(&self, paths: Vec<PathBuf>) {
+        self.show_page("files");
+        if let Some(ref view) = *self.imp().files_view.borrow() {
+            for path in paths {
+                view.upload_file(path);
+            }
+        }
+    }
+
+    /// Write pending form drafts before the window goes away
+    fn save_drafts(&self) {
+        let imp = self.imp();
+        if let Some(ref view) = *imp.links_view.borrow() {
+            view.save_draft();
+        }
+        if let Some(ref view) = *imp.texts_view.borrow() {
+            view.save_draft();
+        }
+    }
+
     fn load_window_state(&self) {
         // Try to load from GSettings, fall back to defaults if schema not installed
         if let Some(schema_source) = gio::SettingsSchemaSource::default() {
@@ -1,8 +1,11 @@
+use crate::format::LinkFormat;
+use crate::media::mime_label;
 use crate::storage::FileEntry;
 use adw::prelude::*;
 use adw::subclass::prelude::*;
-use gtk::glib;
+use gtk::{gdk, gio, glib};
 use std::cell::RefCell;
+use std::path::Path;
 
 mod imp {
     use super::*;
@@ -15,10 +18,64 @@ mod imp {
             <style>
               <class name="see-row"/>
             </style>
+            <child type="prefix">
+              <object class="GtkImage" id="type_icon">
+                <property name="icon-name">text-x-generic-symbolic</property>
+                <property name="valign">center</property>
+                <style>
+                  <class name="dim-label"/>
+                </style>
+              </object>
+            </child>
             <child type="suffix">
               <object class="GtkBox">
                 <property name="spacing">4</property>
                 <property name="margin-start">8</property>
+                <child>
+                  <object class="GtkImage" id="metadata_badge">
+                    <property name="icon-name">security-high-symbolic</property>
+                    <property name="valign">center</property>
+                    <property name="margin-end">4</property>
+                    <property name="visible">False</property>
+                    <property name="tooltip-text" translatable="yes">Image metadata was removed before upload</property>
+                    <style>
+                      <class name="dim-label"/>
+                    </style>
+                  </object>
+                </child>
+                <child>
+                  <object class="GtkImage" id="encrypted_badge">
+                    <property name="icon-name">channel-secure-symbolic</property>
+                    <property name="valign">center</property>
+                    <property name="margin-end">4</property>
+                    <property name="visible">False</property>
+                    <property name="tooltip-text" translatable="yes">End-to-end encrypted</property>
+                    <style>
+                      <class name="dim-label"/>
+                    </style>
+                  </object>
+                </child>
+                <child>
+                  <object class="GtkButton" id="download_button">
+                    <property name="icon-name">folder-download-symbolic</property>
+                    <property name="valign">center</property>
+                    <property name="visible">False</property>
+                    <property name="tooltip-text" translatable="yes">Download &amp; Decrypt</property>
+                    <style>
+                      <class name="flat"/>
+                    </style>
+                  </object>
+                </child>
+                <child>
+                  <object class="GtkMenuButton" id="copy_as_button">
+                    <property name="icon-name">edit-copy-symbolic</property>
+                    <property name="valign">center</property>
+                    <property name="tooltip-text" translatable="yes">Copy as…</property>
+                    <style>
+                      <class name="flat"/>
+                    </style>
+                  </object>
+                </child>
                 <child>
                   <object class="GtkButton" id="qr_button">
                     <property name="icon-name">see-qr-code-symbolic</property>
@@ -69,6 +126,16 @@ mod imp {
         </interface>
     "#)]
     pub struct FileRow {
+        #[template_child]
+        pub type_icon: TemplateChild<gtk::Image>,
+        #[template_child]
+        pub metadata_badge: TemplateChild<gtk::Image>,
+        #[template_child]
+        pub encrypted_badge: TemplateChild<gtk::Image>,
+        #[template_child]
+        pub download_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub copy_as_button: TemplateChild<gtk::MenuButton>,
         #[template_child]
         pub qr_button: TemplateChild<gtk::Button>,
         #[template_child]
@@ -77,6 +144,7 @@ mod imp {
         pub copy_button: TemplateChild<gtk::Button>,
         #[template_child]
         pub delete_button: TemplateChild<gtk::Button>,
+        pub copy_as_action: RefCell<Option<gio::SimpleAction>>,
         pub entry: RefCell<Option<FileEntry>>,
     }
 
@@ -112,24 +180,112 @@ impl FileRow {
         let row: Self = glib::Object::new();
         row.set_title(&entry.filename);
 
-        // Show page URL and direct URL with size on separate lines
+        // Show page URL and direct URL with file details on separate lines
         let subtitle = if let Some(ref page_url) = entry.page_url {
             format!(
-                "Share: {}\nDirect: {}\nSize: {}",
+                "Share: {}\nDirect: {}\n{}",
                 page_url,
                 entry.url,
-                Self::format_size(entry.size)
+                Self::format_details(entry)
             )
         } else {
-            format!("Direct: {}\nSize: {}", entry.url, Self::format_size(entry.size))
+            format!("Direct: {}\n{}", entry.url, Self::format_details(entry))
         };
         row.set_subtitle(&subtitle);
         row.set_subtitle_lines(3);
+        row.imp()
+            .type_icon
+            .set_icon_name(Some(Self::icon_name(entry.mime_type.as_deref())));
+        let encrypted = entry.encryption_key.is_some();
+        row.imp().metadata_badge.set_visible(entry.metadata_stripped);
+        row.imp().encrypted_badge.set_visible(encrypted);
+        row.imp().download_button.set_visible(encrypted);
+        // Embedding an encrypted blob is pointless, its link only works in the app
+        row.imp().copy_as_button.set_visible(!encrypted);
+        row.setup_copy_as_menu();
 
         row.imp().entry.replace(Some(entry.clone()));
         row
     }
 
+    fn setup_copy_as_menu(&self) {
+        let action = gio::SimpleAction::new("copy-as", Some(glib::VariantTy::STRING));
+        let group = gio::SimpleActionGroup::new();
+        group.add_action(&action);
+        self.insert_action_group("row", Some(&group));
+
+        let menu = gio::Menu::new();
+        let sections: [&[LinkFormat]; 4] = [
+            &[LinkFormat::Direct, LinkFormat::SharePage],
+            &[
+                LinkFormat::Bbcode,
+                LinkFormat::BbcodeWithLink,
+                LinkFormat::BbcodeDirectLink,
+            ],
+            &[
+                LinkFormat::Html,
+                LinkFormat::HtmlWithLink,
+                LinkFormat::HtmlDirectLink,
+            ],
+            &[LinkFormat::Markdown],
+        ];
+        for formats in sections {
+            let section = gio::Menu::new();
+            for format in formats {
+                let item = gio::MenuItem::new(Some(format.label()), None);
+                item.set_action_and_target_value(
+                    Some("row.copy-as"),
+                    Some(&format.as_str().to_variant()),
+                );
+                section.append_item(&item);
+            }
+            menu.append_section(None, &section);
+        }
+
+        self.imp().copy_as_button.set_menu_model(Some(&menu));
+        self.imp().copy_as_action.replace(Some(action));
+    }
+
+    /// Type, dimensions or page count, and size, like "PNG · 1920×1080 · 2.4 MB"
+    fn format_details(entry: &FileEntry) -> String {
+        let Some(ref mime_type) = entry.mime_type else {
+            return format!("Size: {}", Self::format_size(entry.size));
+        };
+
+        let mut parts = vec![mime_label(mime_type)];
+        if let (Some(width), Some(height)) = (entry.width, entry.height) {
+            parts.push(format!("{}×{}", width, height));
+        }
+        match entry.page_count {
+            Some(1) => parts.push("1 page".to_string()),
+            Some(count) => parts.push(format!("{} pages", count)),
+            None => {}
+        }
+        parts.push(Self::format_size(entry.size));
+        parts.join(" · ")
+    }
+
+    /// Symbolic icon for a content type
+    pub fn icon_name(mime_type: Option<&str>) -> &'static str {
+        let Some(mime_type) = mime_type else {
+            return "text-x-generic-symbolic";
+        };
+
+        match mime_type.split('/').next() {
+            Some("image") => "image-x-generic-symbolic",
+            Some("video") => "video-x-generic-symbolic",
+            Some("audio") => "audio-x-generic-symbolic",
+            _ => match mime_type {
+                "application/pdf" => "x-office-document-symbolic",
+                "application/zip"
+                | "application/gzip"
+                | "application/x-7z-compressed"
+                | "application/vnd.rar" => "package-x-generic-symbolic",
+                _ => "text-x-generic-symbolic",
+            },
+        }
+    }
+
     fn format_size(size: u64) -> String {
         const KB: u64 = 1024;
         const MB: u64 = KB * 1024;
@@ -146,10 +302,33 @@ impl FileRow {
         }
     }
 
+    /// Replace the type icon with a thumbnail of the file
+    pub fn set_thumbnail(&self, path: &Path) {
+        let icon = &self.imp().type_icon;
+        match gdk::Texture::from_filename(path) {
+            Ok(texture) => {
+                icon.set_paintable(Some(&texture));
+                icon.set_pixel_size(48);
+                icon.remove_css_class("dim-label");
+                icon.add_css_class("see-thumbnail");
+            }
+            Err(e) => log::warn!("Failed to load thumbnail: {}", e),
+        }
+    }
+
     pub fn entry(&self) -> Option<FileEntry> {
         self.imp().entry.borrow().clone()
     }
 
+    pub fn download_button(&self) -> &gtk::Button {
+        &self.imp().download_button
+    }
+
+    /// Activated with the id of a [`LinkFormat`] chosen from the "Copy as…" menu
+    pub fn copy_as_action(&self) -> Option<gio::SimpleAction> {
+        self.imp().copy_as_action.borrow().clone()
+    }
+
     pub fn qr_button(&self) -> &gtk::Button {
         &self.imp().qr_button
     }
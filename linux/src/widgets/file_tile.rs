@@ -0,0 +1,119 @@
+use crate::storage::FileEntry;
+use adw::subclass::prelude::*;
+use gtk::prelude::*;
+use gtk::{gdk, glib};
+use std::cell::RefCell;
+use std::path::Path;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate)]
+    #[template(string = r#"
+        <interface>
+          <template class="SeeFileTile" parent="GtkFlowBoxChild">
+            <style>
+              <class name="see-tile"/>
+            </style>
+            <child>
+              <object class="GtkBox">
+                <property name="orientation">vertical</property>
+                <property name="spacing">6</property>
+                <child>
+                  <object class="GtkPicture" id="picture">
+                    <property name="content-fit">cover</property>
+                    <property name="can-shrink">True</property>
+                    <property name="width-request">150</property>
+                    <property name="height-request">112</property>
+                    <style>
+                      <class name="see-tile-picture"/>
+                    </style>
+                  </object>
+                </child>
+                <child>
+                  <object class="GtkImage" id="placeholder">
+                    <property name="icon-name">text-x-generic-symbolic</property>
+                    <property name="pixel-size">48</property>
+                    <property name="width-request">150</property>
+                    <property name="height-request">112</property>
+                    <style>
+                      <class name="dim-label"/>
+                    </style>
+                  </object>
+                </child>
+                <child>
+                  <object class="GtkLabel" id="name_label">
+                    <property name="ellipsize">middle</property>
+                    <property name="max-width-chars">18</property>
+                  </object>
+                </child>
+              </object>
+            </child>
+          </template>
+        </interface>
+    "#)]
+    pub struct FileTile {
+        #[template_child]
+        pub picture: TemplateChild<gtk::Picture>,
+        #[template_child]
+        pub placeholder: TemplateChild<gtk::Image>,
+        #[template_child]
+        pub name_label: TemplateChild<gtk::Label>,
+        pub entry: RefCell<Option<FileEntry>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for FileTile {
+        const NAME: &'static str = "SeeFileTile";
+        type Type = super::FileTile;
+        type ParentType = gtk::FlowBoxChild;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for FileTile {}
+    impl WidgetImpl for FileTile {}
+    impl FlowBoxChildImpl for FileTile {}
+}
+
+glib::wrapper! {
+    pub struct FileTile(ObjectSubclass<imp::FileTile>)
+        @extends gtk::Widget, gtk::FlowBoxChild;
+}
+
+impl FileTile {
+    pub fn new(entry: &FileEntry, icon_name: &str) -> Self {
+        let tile: Self = glib::Object::new();
+        let imp = tile.imp();
+
+        imp.name_label.set_label(&entry.filename);
+        imp.placeholder.set_icon_name(Some(icon_name));
+        imp.picture.set_visible(false);
+        tile.set_tooltip_text(Some(&entry.filename));
+
+        imp.entry.replace(Some(entry.clone()));
+        tile
+    }
+
+    pub fn entry(&self) -> Option<FileEntry> {
+        self.imp().entry.borrow().clone()
+    }
+
+    pub fn set_thumbnail(&self, path: &Path) {
+        let imp = self.imp();
+        match gdk::Texture::from_filename(path) {
+            Ok(texture) => {
+                imp.picture.set_paintable(Some(&texture));
+                imp.picture.set_visible(true);
+                imp.placeholder.set_visible(false);
+            }
+            Err(e) => log::warn!("Failed to load thumbnail: {}", e),
+        }
+    }
+}
@@ -1,8 +1,9 @@
 use crate::storage::LinkEntry;
 use adw::prelude::*;
 use adw::subclass::prelude::*;
-use gtk::glib;
+use gtk::{gdk, glib};
 use std::cell::RefCell;
+use std::path::Path;
 
 mod imp {
     use super::*;
@@ -15,6 +16,16 @@ mod imp {
             <style>
               <class name="see-row"/>
             </style>
+            <child type="prefix">
+              <object class="GtkImage" id="favicon">
+                <property name="visible">False</property>
+                <property name="pixel-size">24</property>
+                <property name="valign">center</property>
+                <style>
+                  <class name="see-favicon"/>
+                </style>
+              </object>
+            </child>
             <child type="suffix">
               <object class="GtkBox">
                 <property name="spacing">4</property>
@@ -58,6 +69,8 @@ mod imp {
         </interface>
     "#)]
     pub struct LinkRow {
+        #[template_child]
+        pub favicon: TemplateChild<gtk::Image>,
         #[template_child]
         pub qr_button: TemplateChild<gtk::Button>,
         #[template_child]
@@ -98,11 +111,31 @@ impl LinkRow {
     pub fn new(entry: &LinkEntry) -> Self {
         let row: Self = glib::Object::new();
         row.set_title(&entry.short_url);
-        row.set_subtitle(&entry.original_url);
+        row.set_subtitle_lines(2);
+        match entry.title {
+            Some(ref title) => row.set_subtitle(&format!(
+                "{}\n{}",
+                glib::markup_escape_text(title),
+                glib::markup_escape_text(&entry.original_url)
+            )),
+            None => row.set_subtitle(&glib::markup_escape_text(&entry.original_url)),
+        }
         row.imp().entry.replace(Some(entry.clone()));
         row
     }
 
+    /// Show the icon of the site the link points to
+    pub fn set_favicon(&self, path: &Path) {
+        let favicon = &self.imp().favicon;
+        match gdk::Texture::from_filename(path) {
+            Ok(texture) => {
+                favicon.set_paintable(Some(&texture));
+                favicon.set_visible(true);
+            }
+            Err(e) => log::warn!("Failed to load favicon: {}", e),
+        }
+    }
+
     pub fn entry(&self) -> Option<LinkEntry> {
         self.imp().entry.borrow().clone()
     }
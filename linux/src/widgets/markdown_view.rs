@@ -0,0 +1,186 @@
+use crate::text::{MarkdownRenderer, MarkdownStyle};
+use adw::subclass::prelude::*;
+use glib::translate::IntoGlib;
+use gtk::prelude::*;
+use gtk::{gdk, gio, glib, pango};
+use std::cell::RefCell;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct MarkdownView {
+        /// Character ranges of the rendered links and where they point
+        pub links: RefCell<Vec<(i32, i32, String)>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MarkdownView {
+        const NAME: &'static str = "SeeMarkdownView";
+        type Type = super::MarkdownView;
+        type ParentType = gtk::TextView;
+    }
+
+    impl ObjectImpl for MarkdownView {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().setup();
+        }
+    }
+
+    impl WidgetImpl for MarkdownView {}
+    impl TextViewImpl for MarkdownView {}
+}
+
+glib::wrapper! {
+    pub struct MarkdownView(ObjectSubclass<imp::MarkdownView>)
+        @extends gtk::Widget, gtk::TextView;
+}
+
+impl MarkdownView {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    fn setup(&self) {
+        self.set_editable(false);
+        self.set_cursor_visible(false);
+        self.set_wrap_mode(gtk::WrapMode::WordChar);
+        self.set_left_margin(16);
+        self.set_right_margin(16);
+        self.set_top_margin(16);
+        self.set_bottom_margin(16);
+        self.add_css_class("see-textview");
+
+        // Open links on click, the preview itself never loads anything
+        let click = gtk::GestureClick::new();
+        click.connect_released(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_, _, x, y| {
+                if let Some(url) = view.link_at(x, y) {
+                    let _ =
+                        gio::AppInfo::launch_default_for_uri(&url, None::<&gio::AppLaunchContext>);
+                }
+            }
+        ));
+        self.add_controller(click);
+
+        let motion = gtk::EventControllerMotion::new();
+        motion.connect_motion(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_, x, y| {
+                let cursor = view.link_at(x, y).map(|_| "pointer");
+                view.set_cursor_from_name(cursor);
+            }
+        ));
+        self.add_controller(motion);
+    }
+
+    /// Replace the preview with rendered Markdown
+    pub fn set_markdown(&self, source: &str) {
+        let buffer = self.buffer();
+        buffer.set_text("");
+
+        // All links share one tag, their targets are kept here by position
+        let mut links = Vec::new();
+        let mut end = buffer.end_iter();
+        for span in MarkdownRenderer::render(source) {
+            let start = end.offset();
+            let tags: Vec<gtk::TextTag> = span.styles.iter().map(|s| self.tag(s)).collect();
+            let tags: Vec<&gtk::TextTag> = tags.iter().collect();
+            buffer.insert_with_tags(&mut end, &span.text, &tags);
+
+            let url = span.styles.iter().find_map(|style| match style {
+                MarkdownStyle::Link(url) => Some(url.clone()),
+                _ => None,
+            });
+            if let Some(url) = url {
+                links.push((start, end.offset(), url));
+            }
+        }
+        self.imp().links.replace(links);
+    }
+
+    fn link_at(&self, x: f64, y: f64) -> Option<String> {
+        let (x, y) = self.window_to_buffer_coords(gtk::TextWindowType::Widget, x as i32, y as i32);
+        let offset = self.iter_at_location(x, y)?.offset();
+        self.imp()
+            .links
+            .borrow()
+            .iter()
+            .find(|(start, end, _)| (*start..*end).contains(&offset))
+            .map(|(_, _, url)| url.clone())
+    }
+
+    /// Text tag for a style, created the first time it is used
+    fn tag(&self, style: &MarkdownStyle) -> gtk::TextTag {
+        let name = match style {
+            MarkdownStyle::Heading(level) => format!("h{}", level),
+            MarkdownStyle::Strong => "strong".to_string(),
+            MarkdownStyle::Emphasis => "emphasis".to_string(),
+            MarkdownStyle::Strikethrough => "strikethrough".to_string(),
+            MarkdownStyle::Code => "code".to_string(),
+            MarkdownStyle::CodeBlock => "code-block".to_string(),
+            MarkdownStyle::Quote => "quote".to_string(),
+            MarkdownStyle::Indent(depth) => format!("indent-{}", depth),
+            MarkdownStyle::Link(_) => "link".to_string(),
+            MarkdownStyle::Rule => "rule".to_string(),
+            MarkdownStyle::TableHeader => "table-header".to_string(),
+        };
+
+        let table = self.buffer().tag_table();
+        if let Some(tag) = table.lookup(&name) {
+            return tag;
+        }
+
+        let shade = gdk::RGBA::new(0.5, 0.5, 0.5, 0.15);
+        let dim = gdk::RGBA::new(0.5, 0.5, 0.5, 1.0);
+        let builder = gtk::TextTag::builder().name(name.as_str());
+        let tag = match style {
+            MarkdownStyle::Heading(level) => builder
+                .weight(pango::Weight::Bold.into_glib())
+                .scale(match level {
+                    1 => 1.8,
+                    2 => 1.5,
+                    3 => 1.25,
+                    4 => 1.1,
+                    _ => 1.0,
+                })
+                .pixels_below_lines(4)
+                .build(),
+            MarkdownStyle::Strong | MarkdownStyle::TableHeader => {
+                builder.weight(pango::Weight::Bold.into_glib()).build()
+            }
+            MarkdownStyle::Emphasis => builder.style(pango::Style::Italic).build(),
+            MarkdownStyle::Strikethrough => builder.strikethrough(true).build(),
+            MarkdownStyle::Code => builder.family("monospace").background_rgba(&shade).build(),
+            MarkdownStyle::CodeBlock => builder
+                .family("monospace")
+                .paragraph_background_rgba(&shade)
+                .wrap_mode(gtk::WrapMode::None)
+                .build(),
+            MarkdownStyle::Quote => builder
+                .style(pango::Style::Italic)
+                .foreground_rgba(&dim)
+                .build(),
+            MarkdownStyle::Indent(depth) => {
+                builder.left_margin(16 + 24 * i32::from(*depth)).build()
+            }
+            MarkdownStyle::Link(_) => builder
+                .underline(pango::Underline::Single)
+                .foreground("#3584e4")
+                .build(),
+            MarkdownStyle::Rule => builder.foreground_rgba(&dim).build(),
+        };
+        table.add(&tag);
+        tag
+    }
+}
+
+impl Default for MarkdownView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,7 +1,11 @@
 mod file_row;
+mod file_tile;
 mod link_row;
+mod markdown_view;
 mod text_row;
 
 pub use file_row::FileRow;
+pub use file_tile::FileTile;
 pub use link_row::LinkRow;
+pub use markdown_view::MarkdownView;
 pub use text_row::TextRow;
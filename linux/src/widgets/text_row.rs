@@ -1,4 +1,5 @@
 use crate::storage::TextEntry;
+use crate::text::Language;
 use adw::prelude::*;
 use adw::subclass::prelude::*;
 use gtk::glib;
@@ -19,6 +20,38 @@ mod imp {
               <object class="GtkBox">
                 <property name="spacing">4</property>
                 <property name="margin-start">8</property>
+                <child>
+                  <object class="GtkLabel" id="syntax_badge">
+                    <property name="valign">center</property>
+                    <property name="margin-end">4</property>
+                    <property name="visible">False</property>
+                    <style>
+                      <class name="see-badge"/>
+                    </style>
+                  </object>
+                </child>
+                <child>
+                  <object class="GtkImage" id="encrypted_badge">
+                    <property name="icon-name">channel-secure-symbolic</property>
+                    <property name="valign">center</property>
+                    <property name="margin-end">4</property>
+                    <property name="visible">False</property>
+                    <property name="tooltip-text" translatable="yes">End-to-end encrypted</property>
+                    <style>
+                      <class name="dim-label"/>
+                    </style>
+                  </object>
+                </child>
+                <child>
+                  <object class="GtkButton" id="view_button">
+                    <property name="icon-name">document-open-symbolic</property>
+                    <property name="valign">center</property>
+                    <property name="tooltip-text" translatable="yes">View Content</property>
+                    <style>
+                      <class name="flat"/>
+                    </style>
+                  </object>
+                </child>
                 <child>
                   <object class="GtkButton" id="qr_button">
                     <property name="icon-name">see-qr-code-symbolic</property>
@@ -69,6 +102,12 @@ mod imp {
         </interface>
     "#)]
     pub struct TextRow {
+        #[template_child]
+        pub syntax_badge: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub encrypted_badge: TemplateChild<gtk::Image>,
+        #[template_child]
+        pub view_button: TemplateChild<gtk::Button>,
         #[template_child]
         pub qr_button: TemplateChild<gtk::Button>,
         #[template_child]
@@ -121,6 +160,16 @@ impl TextRow {
         };
         row.set_subtitle(&subtitle);
         row.set_subtitle_lines(2);
+        row.imp()
+            .encrypted_badge
+            .set_visible(entry.encryption_key.is_some());
+
+        // Older entries may carry ids this version does not know, show those as is
+        if let Some(ref syntax) = entry.syntax {
+            let name = Language::by_id(syntax).map_or(syntax.as_str(), |l| l.name);
+            row.imp().syntax_badge.set_label(name);
+            row.imp().syntax_badge.set_visible(true);
+        }
 
         row.imp().entry.replace(Some(entry.clone()));
         row
@@ -130,6 +179,10 @@ impl TextRow {
         self.imp().entry.borrow().clone()
     }
 
+    pub fn view_button(&self) -> &gtk::Button {
+        &self.imp().view_button
+    }
+
     pub fn qr_button(&self) -> &gtk::Button {
         &self.imp().qr_button
     }